@@ -0,0 +1,128 @@
+//! Compares the ray marching loops of [`march`].
+//!
+//! Run with `cargo bench`.
+
+#![feature(test, portable_simd, platform_intrinsics)]
+#![allow(dead_code)]
+
+extern crate test;
+
+#[path = "../src/march.rs"]
+mod march;
+#[path = "../src/ray.rs"]
+mod ray;
+#[path = "../src/vector/mod.rs"]
+mod vector;
+
+use std::simd::f32x8;
+
+use march::MarchSettings;
+use ray::Ray;
+use test::{black_box, Bencher};
+use vector::prelude::*;
+
+/// How many rays are traced on each side of the image.
+const SIZE: usize = 64;
+
+/// A floor with a row of spheres on it, with lots of rays passing close to the spheres.
+fn map<V: Comp<3>>(p: V::Vec) -> V {
+    let [x, y, z] = p.to_array();
+    let floor = y + V::ONE;
+    // repeat the sphere along the x axis
+    let rx = x - (x / V::from(3.0) + V::from(0.5)).floor() * V::from(3.0);
+    let sphere = vec3(rx, y, z + V::from(5.0)).length() - V::ONE;
+    floor.min(sphere)
+}
+
+/// Rays from the camera at the origin, looking down the negative z axis.
+fn rays() -> Vec<Ray<f32>> {
+    let mut rays = Vec::with_capacity(SIZE * SIZE);
+    for j in 0..SIZE {
+        for i in 0..SIZE {
+            let u = (i as f32 + 0.5) / SIZE as f32 * 2.0 - 1.0;
+            let v = 1.0 - (j as f32 + 0.5) / SIZE as f32 * 2.0;
+            rays.push(Ray::new(Vec3::ZERO, vec3(u, v, -1.0)));
+        }
+    }
+    rays
+}
+
+/// The same rays as [`rays`], with 8 rays in each.
+fn rays_x8() -> Vec<Ray<f32x8>> {
+    rays()
+        .chunks_exact(8)
+        .map(|rays| {
+            let lanes = |f: fn(&Ray<f32>) -> f32| {
+                f32x8::from_array(core::array::from_fn(|lane| f(&rays[lane])))
+            };
+            Ray::new(
+                vec3(
+                    lanes(|r| r.origin.x),
+                    lanes(|r| r.origin.y),
+                    lanes(|r| r.origin.z),
+                ),
+                vec3(lanes(|r| r.dir.x), lanes(|r| r.dir.y), lanes(|r| r.dir.z)),
+            )
+        })
+        .collect()
+}
+
+/// Settings for the standard loops.
+fn standard() -> MarchSettings {
+    MarchSettings {
+        relaxation: Some(0.0),
+        ..MarchSettings::default()
+    }
+}
+
+/// Settings for the relaxed loops, `0.87` is the value suggested by the paper.
+fn relaxed() -> MarchSettings {
+    MarchSettings {
+        relaxation: Some(0.87),
+        ..MarchSettings::default()
+    }
+}
+
+#[bench]
+fn scalar_standard(b: &mut Bencher) {
+    let rays = rays();
+    let settings = standard();
+    b.iter(|| {
+        for &ray in &rays {
+            black_box(march::trace(&map::<f32>, ray, &settings).distance);
+        }
+    });
+}
+
+#[bench]
+fn scalar_relaxed(b: &mut Bencher) {
+    let rays = rays();
+    let settings = relaxed();
+    b.iter(|| {
+        for &ray in &rays {
+            black_box(march::trace(&map::<f32>, ray, &settings).distance);
+        }
+    });
+}
+
+#[bench]
+fn simd_standard(b: &mut Bencher) {
+    let rays = rays_x8();
+    let settings = standard();
+    b.iter(|| {
+        for &ray in &rays {
+            black_box(march::trace(&map::<f32x8>, ray, &settings).distance);
+        }
+    });
+}
+
+#[bench]
+fn simd_relaxed(b: &mut Bencher) {
+    let rays = rays_x8();
+    let settings = relaxed();
+    b.iter(|| {
+        for &ray in &rays {
+            black_box(march::trace(&map::<f32x8>, ray, &settings).distance);
+        }
+    });
+}
@@ -0,0 +1,48 @@
+use super::*;
+use core::f32::consts::PI;
+
+/// A 360 degree panorama, mapping longitude to `u` and latitude to `v`.
+///
+/// The whole view fits in the image, which should be twice as wide as it is tall.
+#[derive(Clone, Copy)]
+pub struct Equirectangular {
+    pub frame: Frame,
+}
+
+impl Equirectangular {
+    /// A camera at `eye` with `target` in the middle of the image, see [`Frame::look_at`].
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self {
+            frame: Frame::look_at(eye, target, up),
+        }
+    }
+
+    /// Rolls the camera anti-clockwise around the direction it's looking by `roll` degrees.
+    #[must_use]
+    pub fn with_roll(self, roll: f32) -> Self {
+        Self {
+            frame: self.frame.with_roll(roll),
+        }
+    }
+
+    /// The size of a pixel when the image is `height` pixels tall,
+    /// which grows with the distance from the eye.
+    pub fn pixel(&self, height: u32) -> Epsilon {
+        // the height of the image goes from straight down to straight up
+        Epsilon::Relative(PI / height as f32)
+    }
+}
+
+impl<V: Comp<2> + Comp<3>> Camera<V> for Equirectangular {
+    #[inline]
+    fn ray(&self, uv: <V as Comp<2>>::Vec, _lens: <V as Comp<2>>::Vec, _aspect: f32) -> Ray<V> {
+        let [u, v] = uv.to_array();
+        let half = V::from(0.5);
+        // longitude goes all the way around, latitude from the bottom to the top
+        let lon = (u - half) * V::from(2.0 * PI);
+        let lat = (v - half) * V::from(PI);
+        let (cos_lat, sin_lat) = (lat.cos(), lat.sin());
+        let dir = vec3(cos_lat * lon.sin(), sin_lat, -cos_lat * lon.cos());
+        self.frame.ray(<V as Comp<3>>::Vec::ZERO, dir)
+    }
+}
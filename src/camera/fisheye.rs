@@ -0,0 +1,69 @@
+use super::*;
+
+/// An equidistant fisheye lens.
+///
+/// The angle between a ray and the middle of the view grows evenly
+/// with it's distance from the middle of the image.
+#[derive(Clone, Copy)]
+pub struct Fisheye {
+    pub frame: Frame,
+    /// Half of the vertical field of view, in radians.
+    half_fov: f32,
+}
+
+impl Fisheye {
+    /// The vertical field of view used by [`Fisheye::look_at`], in degrees.
+    pub const FOV: f32 = 180.0;
+
+    /// A camera at `eye` looking towards `target`, see [`Frame::look_at`].
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self {
+            frame: Frame::look_at(eye, target, up),
+            half_fov: 0.0,
+        }
+        .with_fov(Self::FOV)
+    }
+
+    /// Sets the vertical field of view, in degrees.
+    ///
+    /// The view can be wider than 180 degrees, to see behind the camera.
+    #[must_use]
+    pub fn with_fov(self, fov: f32) -> Self {
+        Self {
+            half_fov: fov.to_radians() * 0.5,
+            ..self
+        }
+    }
+
+    /// Rolls the camera anti-clockwise around the direction it's looking by `roll` degrees.
+    #[must_use]
+    pub fn with_roll(self, roll: f32) -> Self {
+        Self {
+            frame: self.frame.with_roll(roll),
+            ..self
+        }
+    }
+
+    /// The size of a pixel when the image is `height` pixels tall,
+    /// which grows with the distance from the eye.
+    pub fn pixel(&self, height: u32) -> Epsilon {
+        // the angle across a pixel is the same all over the image
+        Epsilon::Relative(2.0 * self.half_fov / height as f32)
+    }
+}
+
+impl<V: Comp<2> + Comp<3>> Camera<V> for Fisheye {
+    #[inline]
+    fn ray(&self, uv: <V as Comp<2>>::Vec, _lens: <V as Comp<2>>::Vec, aspect: f32) -> Ray<V> {
+        let (x, y) = plane::<V>(uv);
+        let x = x * V::from(aspect);
+        // distance from the middle of the image, 1 at the top and bottom edges
+        let r = (x * x + y * y).sqrt();
+        // angle away from the middle of the view
+        let theta = r * V::from(self.half_fov);
+        // sin(theta) / r, avoiding the division by zero in the middle
+        let s = theta.sin() / r.max(V::from(1e-6));
+        let dir = vec3(x * s, y * s, -theta.cos());
+        self.frame.ray(<V as Comp<3>>::Vec::ZERO, dir)
+    }
+}
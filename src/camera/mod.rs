@@ -0,0 +1,156 @@
+//! Cameras that generate the rays shot into the scene.
+
+mod equirectangular;
+mod fisheye;
+mod orthographic;
+mod perspective;
+mod thin_lens;
+
+pub use equirectangular::*;
+pub use fisheye::*;
+pub use orthographic::*;
+pub use perspective::*;
+pub use thin_lens::*;
+
+use crate::march::Epsilon;
+use crate::ray::Ray;
+use crate::vector::prelude::*;
+
+/// Generates a ray for each point on the screen.
+pub trait Camera<V: Comp<2> + Comp<3>> {
+    /// The ray through `uv` on the screen,
+    /// from `(0, 0)` at the bottom left to `(1, 1)` at the top right.
+    ///
+    /// `lens` is a random point from `0` to `1` on both axes,
+    /// that cameras with a lens use to pick where the ray starts.
+    ///
+    /// `aspect` is the width of the image divided by it's height.
+    fn ray(&self, uv: <V as Comp<2>>::Vec, lens: <V as Comp<2>>::Vec, aspect: f32) -> Ray<V>;
+}
+
+/// Any of the cameras, chosen at runtime.
+#[derive(Clone, Copy)]
+pub enum Projection {
+    Perspective(Perspective),
+    Orthographic(Orthographic),
+    Fisheye(Fisheye),
+    Equirectangular(Equirectangular),
+    ThinLens(ThinLens),
+}
+
+impl<V: Comp<2> + Comp<3>> Camera<V> for Projection {
+    #[inline]
+    fn ray(&self, uv: <V as Comp<2>>::Vec, lens: <V as Comp<2>>::Vec, aspect: f32) -> Ray<V> {
+        match self {
+            Projection::Perspective(c) => c.ray(uv, lens, aspect),
+            Projection::Orthographic(c) => c.ray(uv, lens, aspect),
+            Projection::Fisheye(c) => c.ray(uv, lens, aspect),
+            Projection::Equirectangular(c) => c.ray(uv, lens, aspect),
+            Projection::ThinLens(c) => c.ray(uv, lens, aspect),
+        }
+    }
+}
+
+impl Projection {
+    /// The size of a pixel when the image is `height` pixels tall.
+    ///
+    /// Surfaces found to within a pixel look the same as ones found exactly.
+    pub fn pixel(&self, height: u32) -> Epsilon {
+        match self {
+            Projection::Perspective(c) => c.pixel(height),
+            Projection::Orthographic(c) => c.pixel(height),
+            Projection::Fisheye(c) => c.pixel(height),
+            Projection::Equirectangular(c) => c.pixel(height),
+            Projection::ThinLens(c) => c.pixel(height),
+        }
+    }
+
+    /// How far in front of the camera the point `t` along the `ray` is,
+    /// along the direction the camera is looking.
+    ///
+    /// Fisheye and equirectangular cameras can see behind themselves,
+    /// so it's the distance from the camera instead.
+    pub fn depth<V: Comp<3>>(&self, ray: Ray<V>, t: V) -> V {
+        let frame = match self {
+            Projection::Perspective(c) => c.frame,
+            Projection::Orthographic(c) => c.frame,
+            Projection::ThinLens(c) => c.frame,
+            Projection::Fisheye(_) | Projection::Equirectangular(_) => return t,
+        };
+        t * ray.dir.dot(from_vec3::<V>(frame.forward()))
+    }
+}
+
+/// Where a camera is and which way it's facing.
+///
+/// The camera looks down it's negative Z axis, with Y pointing up.
+#[derive(Clone, Copy)]
+pub struct Frame {
+    pub eye: Vec3,
+    /// Rotates directions from camera space into world space.
+    axes: Mat3<f32>,
+}
+
+impl Frame {
+    /// A camera at `eye` looking towards `target`.
+    ///
+    /// `up` is roughly the up direction of the image.
+    /// When it points the same way as the camera is looking, another axis is used instead.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = (target - eye).normalise();
+        let right = cross::<f32>(forward, up);
+        // looking straight along `up` doesn't say which way is right,
+        // so use the Y axis, or the Z axis when looking straight up or down
+        let right = if right.length_sq() < 1e-6 {
+            let up = if forward.y.abs() < 0.999 {
+                Vec3::new(0.0, 1.0, 0.0)
+            } else {
+                Vec3::new(0.0, 0.0, 1.0)
+            };
+            cross::<f32>(forward, up)
+        } else {
+            right
+        };
+        let right = right.normalise();
+        let up = cross::<f32>(right, forward);
+        Self {
+            eye,
+            axes: Mat3::from_cols(right, up, -forward),
+        }
+    }
+
+    /// Rolls the camera anti-clockwise around the direction it's looking by `roll` degrees.
+    #[must_use]
+    pub fn with_roll(self, roll: f32) -> Self {
+        let roll = Mat3::from_axis_angle(vec3(0.0, 0.0, 1.0), roll.to_radians());
+        Self {
+            axes: self.axes * roll,
+            ..self
+        }
+    }
+
+    /// The direction the camera is looking.
+    pub fn forward(&self) -> Vec3 {
+        -self.axes.z_axis
+    }
+
+    /// A ray starting at `origin` going in direction `dir`, both in camera space.
+    #[inline]
+    fn ray<V: Comp<3>>(&self, origin: V::Vec, dir: V::Vec) -> Ray<V> {
+        let axes = Mat3::<V>::from_cols(
+            from_vec3::<V>(self.axes.x_axis),
+            from_vec3::<V>(self.axes.y_axis),
+            from_vec3::<V>(self.axes.z_axis),
+        );
+        let eye = from_vec3::<V>(self.eye);
+        Ray::new(eye + axes.mul_vec3(origin), axes.mul_vec3(dir))
+    }
+}
+
+/// Maps `uv` onto the image plane, from -1 to 1 on both axes.
+#[inline]
+fn plane<V: Comp<2>>(uv: V::Vec) -> (V, V) {
+    let [u, v] = uv.to_array();
+    let two = V::from(2.0);
+    (u * two - V::ONE, v * two - V::ONE)
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+/// A camera without perspective, all rays are parallel.
+///
+/// Objects stay the same size however far away they are.
+#[derive(Clone, Copy)]
+pub struct Orthographic {
+    pub frame: Frame,
+    /// Half of the height of the view, in world units.
+    half_height: f32,
+}
+
+impl Orthographic {
+    /// The height of the view used by [`Orthographic::look_at`], in world units.
+    pub const HEIGHT: f32 = 2.0;
+
+    /// A camera at `eye` looking towards `target`, see [`Frame::look_at`].
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self {
+            frame: Frame::look_at(eye, target, up),
+            half_height: Self::HEIGHT * 0.5,
+        }
+    }
+
+    /// Sets how much of the world fits in the height of the image, in world units.
+    #[must_use]
+    pub fn with_height(self, height: f32) -> Self {
+        Self {
+            half_height: height * 0.5,
+            ..self
+        }
+    }
+
+    /// Rolls the camera anti-clockwise around the direction it's looking by `roll` degrees.
+    #[must_use]
+    pub fn with_roll(self, roll: f32) -> Self {
+        Self {
+            frame: self.frame.with_roll(roll),
+            ..self
+        }
+    }
+
+    /// The size of a pixel when the image is `height` pixels tall,
+    /// the same however far away it is.
+    pub fn pixel(&self, height: u32) -> Epsilon {
+        Epsilon::Absolute(2.0 * self.half_height / height as f32)
+    }
+}
+
+impl<V: Comp<2> + Comp<3>> Camera<V> for Orthographic {
+    #[inline]
+    fn ray(&self, uv: <V as Comp<2>>::Vec, _lens: <V as Comp<2>>::Vec, aspect: f32) -> Ray<V> {
+        let (x, y) = plane::<V>(uv);
+        let x = x * V::from(aspect * self.half_height);
+        let y = y * V::from(self.half_height);
+        // rays start across the plane of the eye, all going forwards
+        let origin = vec3(x, y, V::ZERO);
+        let dir = vec3(V::ZERO, V::ZERO, -V::ONE);
+        self.frame.ray(origin, dir)
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+/// A pinhole camera, where everything is in focus.
+#[derive(Clone, Copy)]
+pub struct Perspective {
+    pub frame: Frame,
+    /// Half of the height of the image plane, one unit in front of the eye.
+    half_height: f32,
+}
+
+impl Perspective {
+    /// The vertical field of view used by [`Perspective::look_at`], in degrees.
+    pub const FOV: f32 = 53.130_1;
+
+    /// A camera at `eye` looking towards `target`, see [`Frame::look_at`].
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self {
+            frame: Frame::look_at(eye, target, up),
+            half_height: 0.0,
+        }
+        .with_fov(Self::FOV)
+    }
+
+    /// Sets the vertical field of view, in degrees.
+    #[must_use]
+    pub fn with_fov(self, fov: f32) -> Self {
+        Self {
+            half_height: (fov.to_radians() * 0.5).tan(),
+            ..self
+        }
+    }
+
+    /// Rolls the camera anti-clockwise around the direction it's looking by `roll` degrees.
+    #[must_use]
+    pub fn with_roll(self, roll: f32) -> Self {
+        Self {
+            frame: self.frame.with_roll(roll),
+            ..self
+        }
+    }
+
+    /// The size of a pixel when the image is `height` pixels tall,
+    /// which grows with the distance from the eye.
+    pub fn pixel(&self, height: u32) -> Epsilon {
+        Epsilon::Relative(2.0 * self.half_height / height as f32)
+    }
+}
+
+impl<V: Comp<2> + Comp<3>> Camera<V> for Perspective {
+    #[inline]
+    fn ray(&self, uv: <V as Comp<2>>::Vec, _lens: <V as Comp<2>>::Vec, aspect: f32) -> Ray<V> {
+        let (x, y) = plane::<V>(uv);
+        let x = x * V::from(aspect * self.half_height);
+        let y = y * V::from(self.half_height);
+        // every ray starts at the eye
+        let dir = vec3(x, y, -V::ONE);
+        self.frame.ray(<V as Comp<3>>::Vec::ZERO, dir)
+    }
+}
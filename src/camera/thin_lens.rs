@@ -0,0 +1,93 @@
+use super::*;
+use core::f32::consts::TAU;
+
+/// A perspective camera with a round lens, so only objects at the focus distance are sharp.
+///
+/// Each ray starts at a different point on the lens,
+/// so a few samples are needed for each pixel to get a smooth blur.
+#[derive(Clone, Copy)]
+pub struct ThinLens {
+    pub frame: Frame,
+    /// Half of the height of the image plane, one unit in front of the eye.
+    half_height: f32,
+    /// Radius of the lens.
+    aperture: f32,
+    /// Distance in front of the lens where objects are in focus.
+    focus: f32,
+}
+
+impl ThinLens {
+    /// The radius of the lens used by [`ThinLens::look_at`].
+    pub const APERTURE: f32 = 0.05;
+
+    /// A camera at `eye` looking towards `target`, see [`Frame::look_at`].
+    ///
+    /// The camera is focused on `target`.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self {
+            frame: Frame::look_at(eye, target, up),
+            half_height: 0.0,
+            aperture: Self::APERTURE,
+            focus: (target - eye).length(),
+        }
+        .with_fov(Perspective::FOV)
+    }
+
+    /// Sets the vertical field of view, in degrees.
+    #[must_use]
+    pub fn with_fov(self, fov: f32) -> Self {
+        Self {
+            half_height: (fov.to_radians() * 0.5).tan(),
+            ..self
+        }
+    }
+
+    /// Sets the radius of the lens, bigger lenses blur more.
+    ///
+    /// A lens with no radius is the same as a [`Perspective`] camera.
+    #[must_use]
+    pub fn with_aperture(self, aperture: f32) -> Self {
+        Self { aperture, ..self }
+    }
+
+    /// Sets the distance in front of the camera that's in focus.
+    #[must_use]
+    pub fn with_focus(self, focus: f32) -> Self {
+        Self { focus, ..self }
+    }
+
+    /// Rolls the camera anti-clockwise around the direction it's looking by `roll` degrees.
+    #[must_use]
+    pub fn with_roll(self, roll: f32) -> Self {
+        Self {
+            frame: self.frame.with_roll(roll),
+            ..self
+        }
+    }
+
+    /// The size of a pixel when the image is `height` pixels tall, see [`Perspective::pixel`].
+    pub fn pixel(&self, height: u32) -> Epsilon {
+        Epsilon::Relative(2.0 * self.half_height / height as f32)
+    }
+}
+
+impl<V: Comp<2> + Comp<3>> Camera<V> for ThinLens {
+    #[inline]
+    fn ray(&self, uv: <V as Comp<2>>::Vec, lens: <V as Comp<2>>::Vec, aspect: f32) -> Ray<V> {
+        let (x, y) = plane::<V>(uv);
+        let x = x * V::from(aspect * self.half_height);
+        let y = y * V::from(self.half_height);
+        // where the pinhole ray meets the plane in focus
+        let focus = V::from(self.focus);
+        let target = vec3(x * focus, y * focus, -focus);
+
+        // pick a point evenly over the area of the lens
+        let [s, t] = lens.to_array();
+        let r = s.sqrt() * V::from(self.aperture);
+        let theta = t * V::from(TAU);
+        let origin = vec3(r * theta.cos(), r * theta.sin(), V::ZERO);
+
+        // every ray through the lens meets at the same point in focus
+        self.frame.ray(origin, target - origin)
+    }
+}
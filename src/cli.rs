@@ -0,0 +1,428 @@
+use std::path::{Path, PathBuf};
+
+use crate::debug::View;
+use crate::fog::{Fog, Media};
+use crate::march::{Occlusion, OcclusionMode, Penumbra, Shadows};
+use crate::path::Integrator;
+use crate::sampling::{Adaptive, Filter, Pattern, Sampler};
+
+/// How to use the `erm` binary, printed for `--help`.
+pub const USAGE: &str = "\
+Usage: erm [OPTIONS]
+
+Options:
+      --width <PIXELS>       width of the output image [default: 1920]
+      --height <PIXELS>      height of the output image [default: 1080]
+  -o, --output <FILE>        where to save the image, the format is taken from the extension [default: out.png]
+  -b, --backend <BACKEND>    which vectors to render with, `scalar` or `simd` [default: simd]
+  -s, --scene <FILE>         a scene file to render instead of the default scene
+      --samples <COUNT>      number of samples taken for each pixel [default: 1]
+      --passes <COUNT>       render the samples this many times and average them,
+                             saving the image after each pass [default: 1]
+      --pattern <PATTERN>    where samples are taken in each pixel,
+                             `grid`, `rotated-grid`, `halton`, `r2` or `jitter` [default: grid]
+      --filter <FILTER>      how samples are weighted,
+                             `box`, `tent`, `gaussian` or `mitchell` [default: box]
+      --adaptive             only take all of the samples for pixels on the edges of objects
+      --contrast <AMOUNT>    how different pixels can be before adaptive sampling finds an edge,
+                             from 0 to 1 [default: 0.1]
+      --shadows <SHADOWS>    how the edges of shadows are softened,
+                             `off`, `hard`, `classic` or `improved` [default: improved]
+      --softness <K>         how sharp soft shadows are, higher is sharper [default: 8]
+      --ao <AO>              where ambient occlusion is sampled,
+                             `off`, `normal` or `hemisphere` [default: normal]
+      --integrator <KIND>    how light is worked out, `whitted`,
+                             or `path` to path trace with a path for each sample [default: whitted]
+      --bounces <COUNT>      how many times rays can be reflected or refracted [default: 3]
+      --environment <ENV>    what's behind the scene, replacing the scene's own,
+                             `black`, `gradient`, `sky` or an `.hdr` image
+      --fog <FOG>            fog between the camera and the scene, replacing the scene's own,
+                             `off`, `distance`, `height` or `media`
+      --epsilon <E>          how close rays get to a surface to hit it [default: 0.001]
+      --epsilon-mode <MODE>  `relative` to how far rays have gone, `absolute`,
+                             or `pixel` to make `--epsilon` a number of pixels [default: relative]
+      --steps <COUNT>        the most steps each ray can take [default: 64]
+      --near <DIST>          how far in front of the camera rays start [default: 0]
+      --far <DIST>           how far rays go before they miss [default: 1000]
+      --relaxation <W>       how much further than is safe each step tries to go, from 0 to 1,
+                             0 is standard sphere tracing [default: 0.87 for `scalar`, 0 for `simd`]
+      --debug-view <VIEW>    render a false color view of how rays are marched instead,
+                             `steps`, `normals`, `depth`, `reasons`, or `lanes` for the `simd` backend
+  -t, --threads <COUNT>      number of threads to render with [default: one per core]
+  -q, --quiet                don't print how long the render took
+  -h, --help                 print this message
+";
+
+/// Which type of vector the image is rendered with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// One pixel at a time with `f32`.
+    Scalar,
+    /// Eight pixels at a time with `f32x8`.
+    Simd,
+}
+
+/// What's behind the scene, picked on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Environment {
+    Black,
+    Gradient,
+    /// A daylight sky, lit by the scene's sun.
+    Sky,
+    /// A Radiance `.hdr` image.
+    Hdr(PathBuf),
+}
+
+/// How `--epsilon` is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpsilonMode {
+    Absolute,
+    Relative,
+    /// A number of pixels, which depends on the camera and the height of the image.
+    Pixel,
+}
+
+/// Options for a render, parsed from the command line.
+#[derive(Debug)]
+pub struct Args {
+    pub width: u32,
+    pub height: u32,
+    pub output: PathBuf,
+    pub backend: Backend,
+    pub scene: Option<PathBuf>,
+    pub samples: u32,
+    pub passes: u32,
+    pub pattern: Pattern,
+    pub filter: Filter,
+    /// `None` takes every sample for every pixel.
+    pub adaptive: Option<Adaptive>,
+    /// `None` turns shadows off.
+    pub shadows: Option<Shadows>,
+    /// `None` turns ambient occlusion off.
+    pub occlusion: Option<Occlusion>,
+    pub integrator: Integrator,
+    pub bounces: u32,
+    /// `None` uses the scene's environment.
+    pub environment: Option<Environment>,
+    /// `None` uses the scene's fog, `Some(None)` turns it off.
+    pub fog: Option<Option<Fog>>,
+    /// `None` uses the scene's, as do the rest of the march settings below.
+    pub epsilon: Option<f32>,
+    pub epsilon_mode: Option<EpsilonMode>,
+    pub steps: Option<u32>,
+    pub near: Option<f32>,
+    pub far: Option<f32>,
+    pub relaxation: Option<f32>,
+    /// `None` renders the scene as normal.
+    pub debug: Option<View>,
+    /// `None` uses rayon's default, one thread per core.
+    pub threads: Option<usize>,
+    pub quiet: bool,
+    pub help: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            output: PathBuf::from("out.png"),
+            backend: Backend::Simd,
+            scene: None,
+            samples: 1,
+            passes: 1,
+            pattern: Pattern::default(),
+            filter: Filter::default(),
+            adaptive: None,
+            shadows: Some(Shadows::default()),
+            occlusion: Some(Occlusion::default()),
+            integrator: Integrator::default(),
+            bounces: 3,
+            environment: None,
+            fog: None,
+            epsilon: None,
+            epsilon_mode: None,
+            steps: None,
+            near: None,
+            far: None,
+            relaxation: None,
+            debug: None,
+            threads: None,
+            quiet: false,
+            help: false,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the arguments passed to this process.
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses a list of arguments, not including the binary name.
+    ///
+    /// Values can either be the next argument (`--width 100`) or joined with an `=` (`--width=100`).
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        // can come before `--adaptive` or `--shadows`, so they're set at the end
+        let mut contrast = None;
+        let mut softness = None;
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg, None),
+            };
+            // takes the value of the current flag
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("missing value for `{flag}`"))
+            };
+
+            match flag.as_str() {
+                "--width" => parsed.width = positive(&flag, &value()?)?,
+                "--height" => parsed.height = positive(&flag, &value()?)?,
+                "-o" | "--output" => parsed.output = PathBuf::from(value()?),
+                "-b" | "--backend" => {
+                    parsed.backend = match value()?.as_str() {
+                        "scalar" => Backend::Scalar,
+                        "simd" => Backend::Simd,
+                        other => {
+                            return Err(format!(
+                                "unknown backend `{other}`, expected `scalar` or `simd`"
+                            ))
+                        }
+                    }
+                }
+                "-s" | "--scene" => parsed.scene = Some(PathBuf::from(value()?)),
+                "--samples" => parsed.samples = positive(&flag, &value()?)?,
+                "--passes" => parsed.passes = positive(&flag, &value()?)?,
+                "--pattern" => {
+                    parsed.pattern = match value()?.as_str() {
+                        "grid" => Pattern::Grid,
+                        "rotated-grid" => Pattern::RotatedGrid,
+                        "halton" => Pattern::Halton,
+                        "r2" => Pattern::R2,
+                        "jitter" => Pattern::Jitter,
+                        other => {
+                            return Err(format!(
+                                "unknown pattern `{other}`, expected `grid`, `rotated-grid`, `halton`, `r2` or `jitter`"
+                            ))
+                        }
+                    }
+                }
+                "--filter" => {
+                    parsed.filter = match value()?.as_str() {
+                        "box" => Filter::Box,
+                        "tent" => Filter::Tent,
+                        "gaussian" => Filter::Gaussian,
+                        "mitchell" => Filter::Mitchell,
+                        other => {
+                            return Err(format!(
+                                "unknown filter `{other}`, expected `box`, `tent`, `gaussian` or `mitchell`"
+                            ))
+                        }
+                    }
+                }
+                "--adaptive" => parsed.adaptive = Some(Adaptive::default()),
+                "--contrast" => {
+                    let value = value()?;
+                    match value.parse::<f32>() {
+                        Ok(n) if (0.0..=1.0).contains(&n) => contrast = Some(n),
+                        _ => {
+                            return Err(format!(
+                                "invalid value `{value}` for `{flag}`, expected a number from 0 to 1"
+                            ))
+                        }
+                    }
+                }
+                "--shadows" => {
+                    let penumbra = match value()?.as_str() {
+                        "off" => None,
+                        "hard" => Some(Penumbra::Hard),
+                        "classic" => Some(Penumbra::Classic),
+                        "improved" => Some(Penumbra::Improved),
+                        other => {
+                            return Err(format!(
+                                "unknown shadows `{other}`, expected `off`, `hard`, `classic` or `improved`"
+                            ))
+                        }
+                    };
+                    parsed.shadows = penumbra.map(|penumbra| Shadows {
+                        penumbra,
+                        ..Shadows::default()
+                    });
+                }
+                "--softness" => softness = Some(positive::<f32>(&flag, &value()?)?),
+                "--ao" => {
+                    let mode = match value()?.as_str() {
+                        "off" => None,
+                        "normal" => Some(OcclusionMode::Normal),
+                        "hemisphere" => Some(OcclusionMode::Hemisphere),
+                        other => {
+                            return Err(format!(
+                                "unknown ao `{other}`, expected `off`, `normal` or `hemisphere`"
+                            ))
+                        }
+                    };
+                    parsed.occlusion = mode.map(|mode| Occlusion {
+                        mode,
+                        ..Occlusion::default()
+                    });
+                }
+                "--integrator" => {
+                    parsed.integrator = match value()?.as_str() {
+                        "whitted" => Integrator::Whitted,
+                        "path" => Integrator::Path,
+                        other => {
+                            return Err(format!(
+                                "unknown integrator `{other}`, expected `whitted` or `path`"
+                            ))
+                        }
+                    }
+                }
+                "--bounces" => {
+                    let value = value()?;
+                    parsed.bounces = value.parse().map_err(|_| {
+                        format!("invalid value `{value}` for `{flag}`, expected a whole number")
+                    })?;
+                }
+                "--environment" => {
+                    parsed.environment = Some(match value()?.as_str() {
+                        "black" => Environment::Black,
+                        "gradient" => Environment::Gradient,
+                        "sky" => Environment::Sky,
+                        path if Path::new(path).extension().map_or(false, |ext| ext == "hdr") => {
+                            Environment::Hdr(PathBuf::from(path))
+                        }
+                        other => {
+                            return Err(format!(
+                                "unknown environment `{other}`, expected `black`, `gradient`, `sky` or an `.hdr` image"
+                            ))
+                        }
+                    })
+                }
+                "--fog" => {
+                    parsed.fog = Some(match value()?.as_str() {
+                        "off" => None,
+                        "distance" => Some(Fog::distance(Fog::DENSITY)),
+                        "height" => Some(Fog::height(Fog::DENSITY, Fog::FALLOFF, 0.0)),
+                        "media" => Some(Fog::Media(Media::new(Fog::DENSITY))),
+                        other => {
+                            return Err(format!(
+                                "unknown fog `{other}`, expected `off`, `distance`, `height` or `media`"
+                            ))
+                        }
+                    })
+                }
+                "--epsilon" => parsed.epsilon = Some(positive(&flag, &value()?)?),
+                "--epsilon-mode" => {
+                    parsed.epsilon_mode = Some(match value()?.as_str() {
+                        "absolute" => EpsilonMode::Absolute,
+                        "relative" => EpsilonMode::Relative,
+                        "pixel" => EpsilonMode::Pixel,
+                        other => {
+                            return Err(format!(
+                                "unknown epsilon mode `{other}`, expected `absolute`, `relative` or `pixel`"
+                            ))
+                        }
+                    })
+                }
+                "--steps" => parsed.steps = Some(positive(&flag, &value()?)?),
+                "--near" => {
+                    let value = value()?;
+                    match value.parse::<f32>() {
+                        Ok(n) if n >= 0.0 => parsed.near = Some(n),
+                        _ => {
+                            return Err(format!(
+                                "invalid value `{value}` for `{flag}`, expected a number that isn't negative"
+                            ))
+                        }
+                    }
+                }
+                "--far" => parsed.far = Some(positive(&flag, &value()?)?),
+                "--relaxation" => {
+                    let value = value()?;
+                    match value.parse::<f32>() {
+                        Ok(n) if (0.0..=1.0).contains(&n) => parsed.relaxation = Some(n),
+                        _ => {
+                            return Err(format!(
+                                "invalid value `{value}` for `{flag}`, expected a number from 0 to 1"
+                            ))
+                        }
+                    }
+                }
+                "--debug-view" => {
+                    parsed.debug = Some(match value()?.as_str() {
+                        "steps" => View::Steps,
+                        "normals" => View::Normals,
+                        "depth" => View::Depth,
+                        "reasons" => View::Reasons,
+                        "lanes" => View::Lanes,
+                        other => {
+                            return Err(format!(
+                                "unknown debug view `{other}`, expected `steps`, `normals`, `depth`, `reasons` or `lanes`"
+                            ))
+                        }
+                    })
+                }
+                "-t" | "--threads" => parsed.threads = Some(positive(&flag, &value()?)?),
+                "-q" | "--quiet" => parsed.quiet = true,
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(format!("unknown argument `{flag}`")),
+            }
+        }
+
+        if let Some(contrast) = contrast {
+            match &mut parsed.adaptive {
+                Some(adaptive) => adaptive.contrast = contrast,
+                None => return Err("`--contrast` can only be used with `--adaptive`".into()),
+            }
+        }
+
+        if let Some(softness) = softness {
+            match &mut parsed.shadows {
+                Some(shadows) => shadows.softness = softness,
+                None => return Err("`--softness` can't be used with `--shadows off`".into()),
+            }
+        }
+
+        if let (Some(near), Some(far)) = (parsed.near, parsed.far) {
+            if far <= near {
+                return Err("`--far` must be greater than `--near`".into());
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// How the samples of each pixel are taken.
+    pub fn sampler(&self) -> Sampler {
+        Sampler {
+            samples: self.samples,
+            pattern: self.pattern,
+            filter: self.filter,
+            pass: 0,
+        }
+    }
+}
+
+/// Parses a number that must be greater than zero.
+fn positive<T>(flag: &str, value: &str) -> Result<T, String>
+where
+    T: std::str::FromStr + Default + PartialOrd,
+{
+    match value.parse::<T>() {
+        // negative numbers are parsed for floats, and NaN isn't greater than anything
+        Ok(n) if n > T::default() => Ok(n),
+        _ => Err(format!(
+            "invalid value `{value}` for `{flag}`, expected a number greater than 0"
+        )),
+    }
+}
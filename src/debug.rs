@@ -0,0 +1,88 @@
+//! False color views of how rays are marched through the scene.
+//!
+//! Instead of lighting the scene, each pixel shows something about the ray through it,
+//! which makes it easy to see where the marcher is doing a lot of work.
+
+use crate::march::{MarchSettings, Reason, Trace};
+use crate::vector::prelude::*;
+use crate::Lanes;
+
+/// What the pixels of a debug view show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    /// How many steps each ray took, from black for none through blue and red to yellow for all of them.
+    Steps,
+    /// The surface normal, with each axis from `-1` to `1` as a color from `0` to `1`.
+    Normals,
+    /// How far in front of the camera surfaces are,
+    /// from black right at the camera to white at the furthest surface.
+    ///
+    /// Rays that don't hit anything are white too.
+    Depth,
+    /// Why rays stopped, green for a hit, blue past the far plane and red when they ran out of steps.
+    Reasons,
+    /// How many steps the packet of each pixel went on for after it's ray had finished,
+    /// in the same colors as [`View::Steps`].
+    ///
+    /// Rays are only marched in packets by the `f32x8` backend,
+    /// and a packet keeps going until all of it's rays have finished.
+    Lanes,
+}
+
+/// The color of the pixels of a `trace` in the `view`, where `nor` is the normal where the rays stopped.
+///
+/// `depth` is how far in front of the camera the rays stopped, see [`Projection::depth`],
+/// and `packet` is the most steps taken by any ray in the packet.
+/// [`View::Depth`] has to be finished with [`depth`] once the furthest surface is known.
+///
+/// [`Projection::depth`]: crate::camera::Projection::depth
+pub fn color<V: Lanes>(
+    view: View,
+    trace: Trace<V, V::Mask>,
+    nor: <V as Comp<3>>::Vec,
+    depth: V,
+    packet: V,
+    settings: &MarchSettings,
+) -> <V as Comp<3>>::Vec {
+    let max = V::from(settings.max_steps as f32);
+    match view {
+        View::Steps => heat::<V>(trace.steps / max),
+        View::Normals => {
+            let half = broadcast::<3, V>(V::from(0.5));
+            let col = nor * half + half;
+            select::<3, V>(trace.hit, col, <V as Comp<3>>::Vec::ZERO)
+        }
+        // misses are marked as negative, as they're further away than any surface
+        View::Depth => broadcast::<3, V>(trace.hit.select(depth, -V::ONE)),
+        View::Reasons => {
+            // each ray in the packet stopped for it's own reason
+            let mut col = <V as Comp<3>>::Vec::ZERO;
+            for lane in 0..V::LANES {
+                let reason = match V::reason(&trace, lane) {
+                    Reason::Hit => vec3(0.1, 0.6, 0.1),
+                    Reason::Far => vec3(0.1, 0.2, 0.6),
+                    Reason::Steps => vec3(0.9, 0.1, 0.1),
+                };
+                V::set_lane(&mut col, lane, reason);
+            }
+            col
+        }
+        View::Lanes => heat::<V>((packet - trace.steps) / max),
+    }
+}
+
+/// Finishes a pixel of [`View::Depth`], when `furthest` is the distance to the furthest surface in the image.
+pub fn depth<V: Comp<3>>(col: <V as Comp<3>>::Vec, furthest: f32) -> <V as Comp<3>>::Vec {
+    let [d, _, _] = col.to_array();
+    let d = d
+        .simd_lt(V::ZERO)
+        .select(V::ONE, d / V::from(furthest.max(f32::EPSILON)));
+    broadcast::<3, V>(d)
+}
+
+/// A color for `x` from `0` to `1`, going from black through blue and red to yellow.
+fn heat<V: Comp<3>>(x: V) -> <V as Comp<3>>::Vec {
+    let x = x.clamp(V::ZERO, V::ONE) * V::from(3.0);
+    let ramp = |from: f32| (x - V::from(from)).clamp(V::ZERO, V::ONE);
+    vec3(ramp(1.0), ramp(2.0), ramp(0.0) - ramp(1.0))
+}
@@ -0,0 +1,395 @@
+//! Light coming from far away, behind everything in the scene.
+//!
+//! Rays that don't hit anything see the environment,
+//! and it lights the objects in the scene as well.
+
+use core::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::simd::f32x8;
+
+use image::codecs::hdr::HdrDecoder;
+use image::error::{ImageError, ParameterError, ParameterErrorKind};
+use image::ImageResult;
+
+use crate::vector::prelude::*;
+
+/// The light arriving from every direction.
+pub struct Environment {
+    pub kind: Kind,
+    /// How bright the environment is, the light from it is multiplied by this.
+    pub intensity: f32,
+    /// The light arriving at a surface facing in each direction, see [`Environment::ambient`].
+    irradiance: Image,
+}
+
+pub enum Kind {
+    /// The same color in every direction.
+    Solid(Vec3),
+    /// Fades from the `bottom` color straight down to the `top` color straight up.
+    Gradient { top: Vec3, bottom: Vec3 },
+    /// A clear daylight sky, see [`Sky`].
+    Sky(Sky),
+    /// An equirectangular image wrapped around the scene, see [`Image`].
+    Image(Image),
+}
+
+impl Environment {
+    /// The color at the top of a [`Kind::Gradient`] when it isn't given.
+    pub const TOP: Vec3 = Vec3::new(0.3, 0.5, 0.9);
+    /// The color at the bottom of a [`Kind::Gradient`] when it isn't given.
+    pub const BOTTOM: Vec3 = Vec3::new(0.9, 0.9, 0.9);
+
+    /// An environment with an intensity of one.
+    pub fn new(kind: Kind) -> Self {
+        // the light reaching a surface only changes slowly with it's normal,
+        // so it's worked out once for a small number of directions
+        let coarse = match &kind {
+            Kind::Image(image) => image.resize(64, 32),
+            kind => Image::from_fn(64, 32, |dir| radiance::<f32>(kind, dir)),
+        };
+        let irradiance = Image::from_fn(32, 16, |nor| coarse.irradiance(nor));
+        Self {
+            kind,
+            intensity: 1.0,
+            irradiance,
+        }
+    }
+
+    /// The same `color` in every direction.
+    pub fn solid(color: Vec3) -> Self {
+        Self::new(Kind::Solid(color))
+    }
+
+    /// Fades from the `bottom` color to the `top` color.
+    pub fn gradient(top: Vec3, bottom: Vec3) -> Self {
+        Self::new(Kind::Gradient { top, bottom })
+    }
+
+    /// A clear sky lit by the sun in the direction `sun`.
+    pub fn sky(sun: Vec3, turbidity: f32) -> Self {
+        Self::new(Kind::Sky(Sky::new(sun, turbidity)))
+    }
+
+    /// An equirectangular `image` wrapped around the scene.
+    pub fn image(image: Image) -> Self {
+        Self::new(Kind::Image(image))
+    }
+
+    /// Sets how bright the environment is.
+    #[must_use]
+    pub fn with_intensity(self, intensity: f32) -> Self {
+        Self { intensity, ..self }
+    }
+
+    /// The light arriving from the direction `dir`.
+    #[inline]
+    pub fn radiance<V: Texel>(&self, dir: <V as Comp<3>>::Vec) -> <V as Comp<3>>::Vec {
+        radiance::<V>(&self.kind, dir) * V::from(self.intensity)
+    }
+
+    /// The average light arriving at a surface with the normal `nor`,
+    /// weighted by the cosine of it's angle to the normal.
+    ///
+    /// Multiplied by the color of a diffuse surface, this is the light it reflects.
+    #[inline]
+    pub fn ambient<V: Texel>(&self, nor: <V as Comp<3>>::Vec) -> <V as Comp<3>>::Vec {
+        self.irradiance.sample::<V>(nor) * V::from(self.intensity)
+    }
+}
+
+impl Default for Environment {
+    /// A black environment, which doesn't give off any light.
+    fn default() -> Self {
+        Self::solid(Vec3::ZERO)
+    }
+}
+
+/// The light arriving from the direction `dir`, before the intensity.
+#[inline]
+fn radiance<V: Texel>(kind: &Kind, dir: <V as Comp<3>>::Vec) -> <V as Comp<3>>::Vec {
+    match kind {
+        Kind::Solid(color) => from_vec3::<V>(*color),
+        Kind::Gradient { top, bottom } => {
+            let [_, y, _] = dir.to_array();
+            let t = (y + V::ONE) * V::from(0.5);
+            from_vec3::<V>(*bottom) * (V::ONE - t) + from_vec3::<V>(*top) * t
+        }
+        Kind::Sky(sky) => sky.radiance::<V>(dir),
+        Kind::Image(image) => image.sample::<V>(dir),
+    }
+}
+
+/// The Preetham daylight sky model, an analytic model fitted to measurements of real skies.
+///
+/// The sun itself isn't drawn, it should be added as a directional light.
+///
+/// https://courses.cs.duke.edu/cps124/fall01/resources/p91-preetham.pdf
+pub struct Sky {
+    /// The direction towards the sun.
+    sun: Vec3,
+    /// The Perez coefficients `A` to `E`, for the luminance `Y` and the chromaticity `x` and `y`.
+    perez: [[f32; 5]; 3],
+    /// `Y`, `x` and `y` straight up, divided by the Perez function there.
+    zenith: [f32; 3],
+}
+
+impl Sky {
+    /// How hazy a clear sky is when it isn't given.
+    pub const TURBIDITY: f32 = 3.0;
+    /// Converts the luminance of the sky, in kilocandelas per square metre, to the brightness of a light.
+    const SCALE: f32 = 0.05;
+
+    /// A sky lit by the sun in the direction `sun`.
+    ///
+    /// `turbidity` is how hazy the air is, from about `2` for a clear day to `10` for a hazy one.
+    pub fn new(sun: Vec3, turbidity: f32) -> Self {
+        let t = turbidity;
+        // the model breaks down once the sun sets
+        let sun = vec3(sun.x, sun.y.max(0.01 * sun.length()), sun.z).normalise();
+        let theta = sun.y.acos();
+
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta);
+        let luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        // cubic polynomials of the sun's angle, one for each power of the turbidity
+        let cubic = |[a, b, c, d]: [f32; 4]| ((a * theta + b) * theta + c) * theta + d;
+        let chromaticity =
+            |[t2, t1, t0]: [[f32; 4]; 3]| t * t * cubic(t2) + t * cubic(t1) + cubic(t0);
+        let x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let zenith = [luminance, x, y];
+        let zenith = core::array::from_fn(|i| zenith[i] / perez_f32(perez[i], 0.0, theta));
+        Self { sun, perez, zenith }
+    }
+
+    /// The light arriving from the direction `dir`.
+    #[inline]
+    pub fn radiance<V: Comp<3>>(&self, dir: V::Vec) -> V::Vec {
+        // below the horizon is the horizon, getting darker like the ground
+        let [_, up, _] = dir.to_array();
+        let ground = (V::ONE + up * V::from(5.0)).clamp(V::from(0.3), V::ONE);
+        let cos_theta = up.max(V::from(0.01));
+        let gamma = dir
+            .dot(from_vec3::<V>(self.sun))
+            .clamp(-V::ONE, V::ONE)
+            .acos();
+
+        let [lum, x, y]: [V; 3] = core::array::from_fn(|i| {
+            V::from(self.zenith[i]) * perez(self.perez[i], cos_theta, gamma)
+        });
+        let lum = lum.max(V::ZERO) * V::from(Self::SCALE) * ground;
+
+        // xyY to XYZ, then to linear sRGB
+        let big_x = x / y * lum;
+        let big_z = (V::ONE - x - y) / y * lum;
+        let rgb = vec3(
+            V::from(3.2406) * big_x - V::from(1.5372) * lum - V::from(0.4986) * big_z,
+            V::from(-0.9689) * big_x + V::from(1.8758) * lum + V::from(0.0415) * big_z,
+            V::from(0.0557) * big_x - V::from(0.2040) * lum + V::from(1.0570) * big_z,
+        );
+        rgb.max(V::Vec::ZERO)
+    }
+}
+
+/// The Perez function, how the sky changes with the angle from straight up `theta`,
+/// and the angle from the sun `gamma`.
+#[inline]
+fn perez<V: Comp<3>>([a, b, c, d, e]: [f32; 5], cos_theta: V, gamma: V) -> V {
+    let cos_gamma = gamma.cos();
+    (V::ONE + V::from(a) * (V::from(b) / cos_theta).exp())
+        * (V::ONE + V::from(c) * (V::from(d) * gamma).exp() + V::from(e) * cos_gamma * cos_gamma)
+}
+
+/// [`perez`] with the angles themselves.
+fn perez_f32(coefficients: [f32; 5], theta: f32, gamma: f32) -> f32 {
+    perez::<f32>(coefficients, theta.cos(), gamma)
+}
+
+/// An image of the light from every direction, in the equirectangular projection.
+///
+/// Longitude goes across the image and latitude goes down it,
+/// the same as the equirectangular camera looking down the negative Z axis.
+pub struct Image {
+    width: usize,
+    height: usize,
+    /// The pixels in rows from the top.
+    pixels: Vec<Vec3>,
+}
+
+impl Image {
+    /// An image from it's `pixels`, in rows from the top.
+    ///
+    /// Panics if the image is empty, as it can't be sampled.
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> Self {
+        assert!(width > 0 && height > 0, "an image can't be empty");
+        assert_eq!(pixels.len(), width * height);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Loads a Radiance `.hdr` image.
+    pub fn load(path: &Path) -> ImageResult<Self> {
+        let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+        let (width, height) = (decoder.metadata().width, decoder.metadata().height);
+        if width == 0 || height == 0 {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+        let mut pixels = vec![Vec3::ZERO; (width * height) as usize];
+        // each pixel is stored as a shared exponent with 8 bits for each channel
+        decoder.read_image_transform(
+            |pixel| {
+                let [r, g, b] = pixel.to_hdr().0;
+                vec3(r, g, b)
+            },
+            &mut pixels,
+        )?;
+        Ok(Self::new(width as usize, height as usize, pixels))
+    }
+
+    /// A `width` by `height` image, with the color `f(dir)` in the middle of each pixel.
+    fn from_fn(width: usize, height: usize, f: impl Fn(Vec3) -> Vec3) -> Self {
+        let pixels = (0..width * height)
+            .map(|i| f(direction(i % width, i / width, width, height)))
+            .collect();
+        Self::new(width, height, pixels)
+    }
+
+    /// Resizes the image to `width` by `height`, averaging the pixels that land on each new pixel.
+    fn resize(&self, width: usize, height: usize) -> Self {
+        // some new pixels would be missed when the image is made bigger, so it's sampled instead
+        if self.width < width || self.height < height {
+            return Self::from_fn(width, height, |dir| self.sample::<f32>(dir));
+        }
+        let mut sum = vec![Vec3::ZERO; width * height];
+        let mut count = vec![0.0; width * height];
+        for (i, pixel) in self.pixels.iter().enumerate() {
+            let x = (i % self.width) * width / self.width;
+            let y = (i / self.width) * height / self.height;
+            sum[y * width + x] += *pixel;
+            count[y * width + x] += 1.0;
+        }
+        let pixels = sum
+            .into_iter()
+            .zip(count)
+            .map(|(sum, count)| if count > 0.0 { sum / count } else { sum })
+            .collect();
+        Self::new(width, height, pixels)
+    }
+
+    /// The light from the whole image reaching a surface with the normal `nor`,
+    /// weighted by the cosine of it's angle to the normal, divided by pi.
+    fn irradiance(&self, nor: Vec3) -> Vec3 {
+        // the pixels near the poles cover less of the sphere
+        let (dlon, dlat) = (TAU / self.width as f32, PI / self.height as f32);
+        let mut sum = Vec3::ZERO;
+        for (i, pixel) in self.pixels.iter().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            let dir = direction(x, y, self.width, self.height);
+            let solid_angle = dir.y.acos().sin() * dlon * dlat;
+            sum += *pixel * (nor.dot(dir).max(0.0) * solid_angle);
+        }
+        sum / PI
+    }
+
+    /// The pixel at `x` and `y`.
+    #[inline]
+    fn pixel(&self, x: usize, y: usize) -> Vec3 {
+        // rounding can push coordinates just past the edge
+        self.pixels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+
+    /// The color in the direction `dir`, blended between the closest four pixels.
+    #[inline]
+    pub fn sample<V: Texel>(&self, dir: <V as Comp<3>>::Vec) -> <V as Comp<3>>::Vec {
+        let (w, h) = (V::from(self.width as f32), V::from(self.height as f32));
+        let half = V::from(0.5);
+        // the inverse of the equirectangular camera
+        let [x, y, z] = dir.to_array();
+        let u = x.atan2(-z) / V::from(TAU) + half;
+        let v = y.clamp(-V::ONE, V::ONE).acos() / V::from(PI);
+
+        let (x, y) = (u * w - half, v * h - half);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        // wrap around horizontally, and stop at the top and bottom
+        let wrap = |x: V| x - (x / w).floor() * w;
+        let (x0, x1) = (wrap(x0), wrap(x0 + V::ONE));
+        let (y0, y1) = (y0.max(V::ZERO), (y0 + V::ONE).min(h - V::ONE));
+
+        let top = V::fetch(self, x0, y0) * (V::ONE - tx) + V::fetch(self, x1, y0) * tx;
+        let bottom = V::fetch(self, x0, y1) * (V::ONE - tx) + V::fetch(self, x1, y1) * tx;
+        top * (V::ONE - ty) + bottom * ty
+    }
+}
+
+/// The direction through the middle of the pixel at `x` and `y`.
+fn direction(x: usize, y: usize, width: usize, height: usize) -> Vec3 {
+    let lon = ((x as f32 + 0.5) / width as f32 - 0.5) * TAU;
+    let lat = FRAC_PI_2 - (y as f32 + 0.5) / height as f32 * PI;
+    vec3(lat.cos() * lon.sin(), lat.sin(), -lat.cos() * lon.cos())
+}
+
+/// Components that pixels of an [`Image`] can be looked up with.
+pub trait Texel: Comp<3> {
+    /// The pixel at `x` and `y` in each lane, which are whole numbers.
+    fn fetch(image: &Image, x: Self, y: Self) -> Self::Vec;
+}
+
+impl Texel for f32 {
+    #[inline]
+    fn fetch(image: &Image, x: f32, y: f32) -> Vec3 {
+        image.pixel(x as usize, y as usize)
+    }
+}
+
+impl Texel for f32x8 {
+    #[inline]
+    fn fetch(image: &Image, x: f32x8, y: f32x8) -> Vec3x8 {
+        // each lane can be anywhere in the image
+        let pixels: [Vec3; 8] = core::array::from_fn(|i| image.pixel(x[i] as usize, y[i] as usize));
+        Vec3x8 {
+            x: f32x8::from_array(pixels.map(|p| p.x)),
+            y: f32x8::from_array(pixels.map(|p| p.y)),
+            z: f32x8::from_array(pixels.map(|p| p.z)),
+        }
+    }
+}
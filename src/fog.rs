@@ -0,0 +1,326 @@
+//! Fog, and light scattered by the air between the camera and the scene.
+//!
+//! Fog is applied along the first ray of each sample,
+//! so far away objects fade into the distance.
+
+use core::f32::consts::PI;
+
+use crate::environment::Texel;
+use crate::light::Incoming;
+use crate::march::{self, Shadows, Trace};
+use crate::random::{self, Rng};
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::sdf::Sdf;
+use crate::vector::prelude::*;
+
+/// How the air between the camera and the scene changes the light passing through it.
+#[derive(Clone, Copy, Debug)]
+pub enum Fog {
+    /// Fades to `color` the further away things are.
+    ///
+    /// https://iquilezles.org/articles/fog/
+    Distance { color: Vec3, density: f32 },
+    /// Fades to `color`, with fog that's thickest at `height` and thins out going up by `falloff`.
+    ///
+    /// https://iquilezles.org/articles/fog/
+    Height {
+        color: Vec3,
+        density: f32,
+        falloff: f32,
+        height: f32,
+    },
+    /// Light scattered towards the camera by particles in the air, see [`Media`].
+    Media(Media),
+}
+
+/// A participating medium, like mist or smoke, which absorbs and scatters light.
+///
+/// The ray is marched through the medium, and at every step light from each light is scattered
+/// towards the camera once, unless it's shadowed by the scene.
+///
+/// https://www.scratchapixel.com/lessons/3d-basic-rendering/volume-rendering-for-developers
+#[derive(Clone, Copy, Debug)]
+pub struct Media {
+    /// How much light is scattered or absorbed over a distance of one.
+    pub density: f32,
+    /// The color of the light that's scattered, the rest is absorbed.
+    pub albedo: Vec3,
+    /// How much light keeps going forwards when it's scattered,
+    /// from `-1` all of it backwards to `1` all of it forwards.
+    pub anisotropy: f32,
+    /// The size of the clumps in the medium, if it's patchy.
+    ///
+    /// `None` is the same density everywhere.
+    pub noise: Option<f32>,
+    /// How many steps are taken along each ray.
+    pub steps: u32,
+}
+
+impl Fog {
+    /// The color of fog when it isn't given.
+    pub const COLOR: Vec3 = Vec3::new(0.5, 0.6, 0.7);
+    /// The density of fog when it isn't given.
+    pub const DENSITY: f32 = 0.1;
+    /// How quickly [`Fog::Height`] thins out going up when it isn't given.
+    pub const FALLOFF: f32 = 1.0;
+
+    /// Fog that fades to the default color with distance.
+    pub fn distance(density: f32) -> Self {
+        Self::Distance {
+            color: Self::COLOR,
+            density,
+        }
+    }
+
+    /// Fog lying on the ground at `height`, see [`Fog::Height`].
+    pub fn height(density: f32, falloff: f32, height: f32) -> Self {
+        Self::Height {
+            color: Self::COLOR,
+            density,
+            falloff,
+            height,
+        }
+    }
+
+    /// Sets the color of [`Fog::Distance`] and [`Fog::Height`] fog.
+    ///
+    /// The color of a [`Fog::Media`] comes from the lights, so it doesn't change.
+    #[must_use]
+    pub fn with_color(self, color: Vec3) -> Self {
+        match self {
+            Self::Distance { density, .. } => Self::Distance { color, density },
+            Self::Height {
+                density,
+                falloff,
+                height,
+                ..
+            } => Self::Height {
+                color,
+                density,
+                falloff,
+                height,
+            },
+            Self::Media(media) => Self::Media(media),
+        }
+    }
+
+    /// The light reaching the origin of the `ray` through the fog,
+    /// when `color` is the light from where the `trace` of the ray ended.
+    ///
+    /// Rays that didn't hit anything carry on forever.
+    /// `shadows` is how the lights shining into a [`Fog::Media`] are shadowed, if at all.
+    pub fn apply<V, S>(
+        &self,
+        scene: &Scene<S>,
+        ray: Ray<V>,
+        trace: Trace<V, V::Mask>,
+        color: <V as Comp<3>>::Vec,
+        rng: &mut Rng<V>,
+        shadows: Option<Shadows>,
+    ) -> <V as Comp<3>>::Vec
+    where
+        V: Comp<2> + Comp<3> + march::Traceable + Texel,
+        S: Sdf<V>,
+    {
+        let Trace { distance, hit, .. } = trace;
+        let (fog, amount) = match *self {
+            Self::Distance { color, density } => {
+                let amount = V::ONE - (-V::from(density) * distance).exp();
+                (color, hit.select(amount, V::ONE))
+            }
+            Self::Height {
+                color,
+                density,
+                falloff,
+                height,
+            } => {
+                let [_, oy, _] = ray.origin.to_array();
+                let [_, dy, _] = ray.dir.to_array();
+                let b = V::from(falloff);
+                // the fog between the camera and the surface, the integral of
+                // `density * exp(-falloff * (y - height))` along the ray
+                let k = b * dy;
+                let t = hit.select(distance, V::from(FAR));
+                // `exp` can't overflow, as that would make infinities and then NaNs
+                let spread = (V::ONE - (-k * t).min(V::from(80.0)).exp()) / k;
+                // looking along the ground, the fog is the same all the way along
+                let level = k.abs().simd_lt(V::from(1e-4));
+                let depth = V::from(density)
+                    * (-b * (oy - V::from(height))).min(V::from(80.0)).exp()
+                    * level.select(t, spread);
+                (color, V::ONE - (-depth).exp())
+            }
+            Self::Media(media) => {
+                return media.apply(scene, ray, trace, color, rng, shadows);
+            }
+        };
+        color * (V::ONE - amount) + from_vec3::<V>(fog) * amount
+    }
+}
+
+/// How far rays that don't hit anything go into fog.
+const FAR: f32 = 1e4;
+
+impl Media {
+    /// How many steps are taken along each ray when it isn't given.
+    pub const STEPS: u32 = 32;
+    /// How far rays are marched into the medium.
+    pub const DISTANCE: f32 = 20.0;
+
+    /// A medium of the same `density` everywhere that scatters all light evenly.
+    pub fn new(density: f32) -> Self {
+        Self {
+            density,
+            albedo: Vec3::ONE,
+            anisotropy: 0.0,
+            noise: None,
+            steps: Self::STEPS,
+        }
+    }
+
+    /// Sets the color of the light that's scattered.
+    #[must_use]
+    pub fn with_albedo(self, albedo: Vec3) -> Self {
+        Self { albedo, ..self }
+    }
+
+    /// Sets how much light keeps going forwards when it's scattered.
+    #[must_use]
+    pub fn with_anisotropy(self, anisotropy: f32) -> Self {
+        Self { anisotropy, ..self }
+    }
+
+    /// Makes the medium patchy, with clumps about `scale` across.
+    #[must_use]
+    pub fn with_noise(self, scale: f32) -> Self {
+        Self {
+            noise: Some(scale),
+            ..self
+        }
+    }
+
+    /// Sets how many steps are taken along each ray.
+    #[must_use]
+    pub fn with_steps(self, steps: u32) -> Self {
+        Self { steps, ..self }
+    }
+
+    /// The density of the medium at the point `p`.
+    #[inline]
+    pub fn density_at<V: Comp<3>>(&self, p: V::Vec) -> V {
+        match self.noise {
+            // the noise averages out at a half
+            Some(scale) => V::from(self.density * 2.0) * fbm::<V>(p / V::from(scale)),
+            None => V::from(self.density),
+        }
+    }
+
+    /// See [`Fog::apply`].
+    fn apply<V, S>(
+        &self,
+        scene: &Scene<S>,
+        ray: Ray<V>,
+        trace: Trace<V, V::Mask>,
+        color: <V as Comp<3>>::Vec,
+        rng: &mut Rng<V>,
+        shadows: Option<Shadows>,
+    ) -> <V as Comp<3>>::Vec
+    where
+        V: Comp<2> + Comp<3> + march::Traceable + Texel,
+        S: Sdf<V>,
+    {
+        let map = |p| scene.root.dist(p);
+        let Trace { distance, hit, .. } = trace;
+        let far = hit
+            .select(distance, V::from(Self::DISTANCE))
+            .min(V::from(Self::DISTANCE));
+        let dt = far / V::from(self.steps as f32);
+        // start each sample at a different point in the first step, which hides banding
+        let [jitter, _] = rng.point().to_array();
+        let albedo = from_vec3::<V>(self.albedo);
+        // light from the environment is scattered evenly, so it's average is enough
+        let up = vec3(V::ZERO, V::ONE, V::ZERO);
+        let ambient = (scene.environment.ambient::<V>(up) + scene.environment.ambient::<V>(-up))
+            * V::from(0.5);
+
+        // how much light makes it through the medium so far
+        let mut transmittance = V::ONE;
+        let mut scattered = <V as Comp<3>>::Vec::ZERO;
+        for step in 0..self.steps {
+            let t = dt * (V::from(step as f32) + jitter);
+            let p = ray.at(t);
+
+            // the light arriving at this point, from every direction
+            let area = rng.point();
+            let incoming = scene.lights.iter().fold(ambient, |sum, light| {
+                let Incoming {
+                    dir,
+                    dist,
+                    radiance,
+                } = light.incoming::<V>(p, area);
+                let visible = match shadows {
+                    Some(shadows) => {
+                        let ray = Ray::new(p, dir);
+                        march::shadow(&map, ray, dist, shadows, &scene.march.secondary())
+                    }
+                    None => V::ONE,
+                };
+                sum + radiance * (visible * phase(ray.dir.dot(dir), self.anisotropy))
+            });
+
+            // the light scattered towards the camera over this step,
+            // and how much of the light from further away is lost
+            let extinction = (-self.density_at::<V>(p) * dt).exp();
+            scattered += incoming * albedo * (transmittance * (V::ONE - extinction));
+            transmittance *= extinction;
+        }
+        color * transmittance + scattered
+    }
+}
+
+/// The Henyey-Greenstein phase function,
+/// how much light is scattered at an angle with the cosine `cos` from it's direction.
+#[inline]
+fn phase<V: Comp<3>>(cos: V, g: f32) -> V {
+    let g2 = V::from(g * g);
+    let denom = V::ONE + g2 - V::from(2.0 * g) * cos;
+    (V::ONE - g2) / (V::from(4.0 * PI) * denom * denom.sqrt())
+}
+
+/// Smooth random values in `[0, 1)`, the same at whole numbers on every axis.
+#[inline]
+fn noise<V: Comp<3>>(p: V::Vec) -> V {
+    let [x, y, z] = p.to_array();
+    let (ix, iy, iz) = (x.floor(), y.floor(), z.floor());
+    // smoothstep between the corners, so there are no creases
+    let smooth = |f: V| f * f * (V::from(3.0) - V::from(2.0) * f);
+    let (fx, fy, fz) = (smooth(x - ix), smooth(y - iy), smooth(z - iz));
+    let corner = |dx: f32, dy: f32, dz: f32| {
+        random::hash3::<V>(vec3(ix + V::from(dx), iy + V::from(dy), iz + V::from(dz)))
+    };
+    let mix = |a: V, b: V, t: V| a + (b - a) * t;
+    let face = |dz| {
+        mix(
+            mix(corner(0.0, 0.0, dz), corner(1.0, 0.0, dz), fx),
+            mix(corner(0.0, 1.0, dz), corner(1.0, 1.0, dz), fx),
+            fy,
+        )
+    };
+    mix(face(0.0), face(1.0), fz)
+}
+
+/// Layers of [`noise`], each half the size and strength of the last.
+#[inline]
+fn fbm<V: Comp<3>>(p: V::Vec) -> V {
+    let mut p = p;
+    let mut sum = V::ZERO;
+    let mut amplitude = 0.5;
+    for _ in 0..4 {
+        sum += noise::<V>(p) * V::from(amplitude);
+        p *= V::from(2.0);
+        amplitude *= 0.5;
+    }
+    // the amplitudes add up to just under one
+    sum / V::from(0.9375)
+}
@@ -0,0 +1,223 @@
+//! Lights that shine on the objects in a scene.
+
+use core::f32::consts::TAU;
+
+use crate::vector::prelude::*;
+
+/// A light with a color and an intensity.
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    pub kind: Kind,
+    pub color: Vec3,
+    /// How bright the light is, the color is multiplied by this.
+    pub intensity: f32,
+}
+
+/// The shape of a light, and where it is.
+#[derive(Clone, Copy, Debug)]
+pub enum Kind {
+    /// A light that's infinitely far away, like the sun.
+    Directional {
+        /// The direction towards the light.
+        dir: Vec3,
+    },
+    /// Light from a single point, falling off with the square of the distance.
+    Point { position: Vec3 },
+    /// A point light that only shines inside of a cone.
+    Spot {
+        position: Vec3,
+        /// The direction the light is pointing.
+        dir: Vec3,
+        /// Cosine of the angle from the middle of the cone where the light starts to fade.
+        cos_inner: f32,
+        /// Cosine of the angle from the middle of the cone where the light ends.
+        cos_outer: f32,
+    },
+    /// A ball of light, which casts soft shadows.
+    Sphere { position: Vec3, radius: f32 },
+    /// A rectangle of light, that only shines out of it's front.
+    Rect {
+        /// The middle of the rectangle.
+        position: Vec3,
+        /// Half of the width of the rectangle, along it's side.
+        u: Vec3,
+        /// Half of the height of the rectangle, along it's top.
+        v: Vec3,
+    },
+}
+
+/// The light arriving at a point from a single light.
+pub struct Incoming<V: Comp<3>> {
+    /// The direction towards the light.
+    pub dir: V::Vec,
+    /// How far away the light is.
+    pub dist: V,
+    /// The color and brightness of the light once it gets to the point.
+    pub radiance: V::Vec,
+}
+
+impl Light {
+    /// The angle from the middle of a spot light's cone where it starts to fade, in degrees.
+    pub const INNER: f32 = 20.0;
+    /// The angle from the middle of a spot light's cone where the light ends, in degrees.
+    pub const OUTER: f32 = 30.0;
+
+    /// A white light with an intensity of one.
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            color: Vec3::ONE,
+            intensity: 1.0,
+        }
+    }
+
+    /// A light infinitely far away in the direction `dir`.
+    pub fn directional(dir: Vec3) -> Self {
+        Self::new(Kind::Directional {
+            dir: dir.normalise(),
+        })
+    }
+
+    /// A light at `position`, shining in every direction.
+    pub fn point(position: Vec3) -> Self {
+        Self::new(Kind::Point { position })
+    }
+
+    /// A light at `position`, shining towards `target` inside of a cone.
+    ///
+    /// The cone has the angles [`Light::INNER`] and [`Light::OUTER`], see [`Light::with_cone`].
+    pub fn spot(position: Vec3, target: Vec3) -> Self {
+        Self::new(Kind::Spot {
+            position,
+            dir: (target - position).normalise(),
+            cos_inner: 0.0,
+            cos_outer: 0.0,
+        })
+        .with_cone(Self::INNER, Self::OUTER)
+    }
+
+    /// A ball of light at `position`.
+    pub fn sphere(position: Vec3, radius: f32) -> Self {
+        Self::new(Kind::Sphere { position, radius })
+    }
+
+    /// A `width` by `height` rectangle of light at `position`, facing towards `target`.
+    ///
+    /// The top of the rectangle is kept level, unless it's facing straight up or down.
+    pub fn rect(position: Vec3, target: Vec3, width: f32, height: f32) -> Self {
+        let normal = (target - position).normalise();
+        let side = if normal.y.abs() > 0.999 {
+            vec3(1.0, 0.0, 0.0)
+        } else {
+            cross::<f32>(vec3(0.0, 1.0, 0.0), normal).normalise()
+        };
+        let top = cross::<f32>(normal, side);
+        Self::new(Kind::Rect {
+            position,
+            u: side * (width * 0.5),
+            v: top * (height * 0.5),
+        })
+    }
+
+    /// Sets the color of the light.
+    #[must_use]
+    pub fn with_color(self, color: Vec3) -> Self {
+        Self { color, ..self }
+    }
+
+    /// Sets how bright the light is.
+    #[must_use]
+    pub fn with_intensity(self, intensity: f32) -> Self {
+        Self { intensity, ..self }
+    }
+
+    /// Sets the angles from the middle of a spot light's cone, in degrees.
+    ///
+    /// The light fades out between `inner` and `outer`. Other lights don't change.
+    #[must_use]
+    pub fn with_cone(self, inner: f32, outer: f32) -> Self {
+        match self.kind {
+            Kind::Spot { position, dir, .. } => Self {
+                kind: Kind::Spot {
+                    position,
+                    dir,
+                    cos_inner: inner.to_radians().cos(),
+                    cos_outer: outer.to_radians().cos(),
+                },
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// The light arriving at the point `p`.
+    ///
+    /// `area` is a random point from `0` to `1` on both axes,
+    /// that lights with an area use to pick where the light comes from.
+    #[inline]
+    pub fn incoming<V: Comp<2> + Comp<3>>(
+        &self,
+        p: <V as Comp<3>>::Vec,
+        area: <V as Comp<2>>::Vec,
+    ) -> Incoming<V> {
+        let color = from_vec3::<V>(self.color * self.intensity);
+        match self.kind {
+            Kind::Directional { dir } => Incoming {
+                dir: from_vec3::<V>(dir),
+                dist: V::from(f32::MAX),
+                radiance: color,
+            },
+            Kind::Point { position } => towards(p, from_vec3::<V>(position), color),
+            Kind::Spot {
+                position,
+                dir,
+                cos_inner,
+                cos_outer,
+            } => {
+                let mut incoming: Incoming<V> = towards(p, from_vec3::<V>(position), color);
+                // smoothly fade out towards the edge of the cone
+                let cos = -incoming.dir.dot(from_vec3::<V>(dir));
+                let t = ((cos - V::from(cos_outer)) / V::from(cos_inner - cos_outer))
+                    .clamp(V::ZERO, V::ONE);
+                incoming.radiance *= t * t * (V::from(3.0) - V::from(2.0) * t);
+                incoming
+            }
+            Kind::Sphere { position, radius } => {
+                // a point on the disk through the middle of the sphere, facing `p`
+                let centre = from_vec3::<V>(position);
+                let (side, top) = basis::<V>((p - centre).normalise());
+                let [s, t] = area.to_array();
+                let r = s.sqrt() * V::from(radius);
+                let theta = t * V::from(TAU);
+                let q = centre + side * (r * theta.cos()) + top * (r * theta.sin());
+                towards(p, q, color)
+            }
+            Kind::Rect { position, u, v } => {
+                let [s, t] = area.to_array();
+                let two = V::from(2.0);
+                let q = from_vec3::<V>(position)
+                    + from_vec3::<V>(u) * (s * two - V::ONE)
+                    + from_vec3::<V>(v) * (t * two - V::ONE);
+                let mut incoming: Incoming<V> = towards(p, q, color);
+                // less light leaves at glancing angles, and none from the back
+                let normal = from_vec3::<V>(cross::<f32>(u, v).normalise());
+                let cos = -incoming.dir.dot(normal);
+                incoming.radiance *= cos.max(V::ZERO);
+                incoming
+            }
+        }
+    }
+}
+
+/// Light of `color` arriving at `p` from the point `q`, falling off with the square of the distance.
+#[inline]
+fn towards<V: Comp<3>>(p: V::Vec, q: V::Vec, color: V::Vec) -> Incoming<V> {
+    let l = q - p;
+    let dist_sq = l.dot(l);
+    let dist = dist_sq.sqrt();
+    Incoming {
+        dir: l / dist,
+        dist,
+        radiance: color / dist_sq,
+    }
+}
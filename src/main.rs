@@ -1,147 +1,618 @@
 #![feature(portable_simd)]
 #![feature(platform_intrinsics)]
 
+mod camera;
+mod cli;
+mod debug;
+mod environment;
+mod fog;
+mod light;
 mod march;
+mod material;
+mod path;
+mod random;
 mod ray;
+mod sampling;
+mod scene;
 mod sdf;
 mod vector;
 
-use march::Trace;
+use camera::Camera;
+use environment::{Environment, Sky};
+use light::{Incoming, Light};
+use march::{Epsilon, Trace};
+use material::Material;
 use ray::Ray;
+use scene::Scene;
 use sdf::prelude::*;
 use vector::prelude::*;
 
 use rayon::prelude::*;
-use std::simd::f32x8;
-
-/// The width of the output image.
-///
-/// For SIMD, must be aligned the LANE count.
-const WIDTH: u32 = round_to_nearest::<{ f32x8::LANES as u32 }>(1920);
-/// The height of the output image.
-const HEIGHT: u32 = 1080;
+use std::path::Path;
+use std::simd::{f32x8, mask32x8};
 
 fn main() {
-    // camera origin
-    let origin = vec3(0.0, 0.0, 2.0).widen();
-    // sun direction
-    let ldir = vec3(1.0, 3.0, 1.0).normalise().widen();
-
-    // a sphere with radius 0.5
-    let sphere = sdf::Sphere(0.5).widen();
-    // the material of the sphere, just a pink color
-    let mat = vec3(0.5, 0.2, 0.5).widen();
-    // our object is static (doesn't move),
-    // we can just return the distance from it
-    let map = |p| sphere.dist(p);
+    let args = match cli::Args::from_env() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}\n\nFor more information, try `--help`.");
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        print!("{}", cli::USAGE);
+        return;
+    }
+    // load the scene file before rendering, so mistakes are reported straight away
+    let scene = args.scene.as_ref().map(|path| {
+        let src = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("error: couldn't read `{}`: {e}", path.display());
+            std::process::exit(1);
+        });
+        // files used by the scene are next to it
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        scene::parse(&src, dir).unwrap_or_else(|e| {
+            eprintln!("error: {}:{e}", path.display());
+            std::process::exit(1);
+        })
+    });
+    // the environment from the command line replaces the scene's
+    let environment = args.environment.as_ref().map(|env| match env {
+        cli::Environment::Black => Environment::default(),
+        cli::Environment::Gradient => Environment::gradient(Environment::TOP, Environment::BOTTOM),
+        cli::Environment::Sky => {
+            // the default scene only has the default sun
+            let lights = scene.as_ref().map_or(&[][..], |scene| &scene.lights[..]);
+            Environment::sky(scene::sun(lights), Sky::TURBIDITY)
+        }
+        cli::Environment::Hdr(path) => {
+            let image = environment::Image::load(path).unwrap_or_else(|e| {
+                eprintln!("error: couldn't load `{}`: {e}", path.display());
+                std::process::exit(1);
+            });
+            Environment::image(image)
+        }
+    });
+    if let Some(threads) = args.threads {
+        // configure the threads used by all of rayon's parallel iterators
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .unwrap();
+    }
 
     // measure time taken to render
     let start = std::time::Instant::now();
 
-    let buf = render(WIDTH, HEIGHT, origin, ldir, mat, map);
+    // the backend decides the type of vector that's used for rendering
+    let buf = match args.backend {
+        cli::Backend::Scalar => run::<f32>(&args, scene, environment),
+        cli::Backend::Simd => run::<f32x8>(&args, scene, environment),
+    };
 
     // print total rendering time
-    eprintln!("rendered in {:#?}", start.elapsed());
+    if !args.quiet {
+        eprintln!("rendered in {:#?}", start.elapsed());
+    }
 
-    // convert to bytes for the image
-    let buf = buf.bytes();
-    // save the image to "out.png"
+    save(&args, &buf);
+}
+
+/// Saves the bytes of an image to the output, the format is guessed from the file extension.
+fn save(args: &cli::Args, buf: &[u8]) {
     image::save_buffer(
-        "out.png",
-        buf.as_slice(),
-        WIDTH,
-        HEIGHT,
+        &args.output,
+        buf,
+        args.width,
+        args.height,
         image::ColorType::Rgb8,
     )
-    .unwrap();
+    .unwrap_or_else(|e| {
+        eprintln!("error: couldn't save `{}`: {e}", args.output.display());
+        std::process::exit(1);
+    });
+}
+
+/// Renders the scene using `V` as the vector components,
+/// returning the bytes of the image.
+///
+/// The default scene is rendered when a scene file wasn't loaded,
+/// and the scene's environment is replaced by `environment` if there is one.
+/// See [`customise`].
+fn run<V>(
+    args: &cli::Args,
+    scene: Option<Scene<sdf::Node>>,
+    environment: Option<Environment>,
+) -> Vec<u8>
+where
+    V: Comp<2>
+        + Comp<3>
+        + sdf::DynComp
+        + environment::Texel
+        + march::Traceable
+        + Positions<Inner = <V as Comp<2>>::Vec>
+        + Lanes,
+    <V as Comp<2>>::Vec: vector::Vector2D<V>,
+    Vec<<V as Comp<3>>::Vec>: ImageBytes,
+{
+    let lighting = Lighting {
+        shadows: args.shadows,
+        occlusion: args.occlusion,
+        bounces: args.bounces,
+        integrator: args.integrator,
+    };
+    match scene {
+        Some(mut scene) => {
+            customise(&mut scene, args, environment);
+            passes::<V, _>(args, lighting, &scene)
+        }
+        None => {
+            let mut scene = default_scene::<V>();
+            customise(&mut scene, args, environment);
+            passes(args, lighting, &scene)
+        }
+    }
+}
+
+/// Renders the `scene` one pass at a time, returning the bytes of the image.
+///
+/// Each pass takes all of the samples again with different random numbers, and is averaged with the ones before.
+/// The image is saved after every pass but the last, so noisy renders can be watched as they clear up.
+fn passes<V, S>(args: &cli::Args, lighting: Lighting, scene: &Scene<S>) -> Vec<u8>
+where
+    S: sdf::MaterialSdf<V> + Sync,
+    V: Comp<2>
+        + Comp<3>
+        + march::Traceable
+        + environment::Texel
+        + Positions<Inner = <V as Comp<2>>::Vec>
+        + Lanes,
+    <V as Comp<2>>::Vec: vector::Vector2D<V>,
+    Vec<<V as Comp<3>>::Vec>: ImageBytes,
+{
+    let mut sum: Vec<<V as Comp<3>>::Vec> = Vec::new();
+    let mut buf = Vec::new();
+    for pass in 0..args.passes {
+        let sampler = sampling::Sampler {
+            pass,
+            ..args.sampler()
+        };
+        let cols = render::<V, S>(
+            args.width,
+            args.height,
+            sampler,
+            args.adaptive,
+            lighting,
+            args.debug,
+            scene,
+        );
+        if sum.is_empty() {
+            sum = cols;
+        } else {
+            sum.iter_mut().zip(cols).for_each(|(sum, col)| *sum += col);
+        }
+
+        // the average of every pass so far, debug views are shown as they are
+        let scale = V::from(1.0 / (pass + 1) as f32);
+        let cols: Vec<_> = sum
+            .par_iter()
+            .map(|&col| match args.debug {
+                Some(_) => col * scale,
+                None => post::<V>(col * scale),
+            })
+            .collect();
+        // convert to bytes for the image
+        buf = cols.bytes(args.width, args.height);
+
+        // the last pass is saved once the render is finished
+        if pass + 1 < args.passes {
+            save(args, &buf);
+            if !args.quiet {
+                eprintln!("finished pass {} of {}", pass + 1, args.passes);
+            }
+        }
+    }
+    buf
 }
 
-/// Renders and returns a color for each pixel for a given `width` and `height`.
-fn render<V>(
+/// Replaces parts of the `scene` with the ones picked on the command line.
+///
+/// `environment` is loaded before rendering, as it can fail.
+/// Exits if the march settings don't make sense once they're combined.
+fn customise<S>(scene: &mut Scene<S>, args: &cli::Args, environment: Option<Environment>) {
+    if let Some(environment) = environment {
+        scene.environment = environment;
+    }
+    if let Some(fog) = args.fog {
+        scene.fog = fog;
+    }
+
+    // any march settings not given on the command line are left as they are
+    let march = &mut scene.march;
+    let (Epsilon::Absolute(epsilon) | Epsilon::Relative(epsilon)) = march.epsilon;
+    march.epsilon = match (args.epsilon_mode, march.epsilon) {
+        (Some(cli::EpsilonMode::Absolute), _) | (None, Epsilon::Absolute(_)) => {
+            Epsilon::Absolute(args.epsilon.unwrap_or(epsilon))
+        }
+        (Some(cli::EpsilonMode::Relative), _) | (None, Epsilon::Relative(_)) => {
+            Epsilon::Relative(args.epsilon.unwrap_or(epsilon))
+        }
+        // the size of a pixel depends on the camera, at the height of the image
+        (Some(cli::EpsilonMode::Pixel), _) => scene
+            .camera
+            .pixel(args.height)
+            .scaled(args.epsilon.unwrap_or(1.0)),
+    };
+    march.max_steps = args.steps.unwrap_or(march.max_steps);
+    march.near = args.near.unwrap_or(march.near);
+    march.far = args.far.unwrap_or(march.far);
+    march.relaxation = args.relaxation.or(march.relaxation);
+    // only one of them might have been given, so check them against the scene's
+    if march.far <= march.near {
+        eprintln!(
+            "error: the far plane ({}) must be further than the near plane ({})",
+            march.far, march.near
+        );
+        std::process::exit(1);
+    }
+}
+
+/// The default scene, built for any type of vector.
+fn default_scene<V: Comp<3>>() -> Scene<impl MaterialSdf<V> + Sync> {
+    // a pink sphere with radius 0.5
+    let sphere = sdf::Sphere(V::from(0.5)).material(0);
+    // a grey floor just below the sphere
+    let floor = sdf::Plane {
+        normal: vec3(V::ZERO, V::ONE, V::ZERO),
+        height: V::from(0.5),
+    }
+    .material(1);
+
+    Scene {
+        camera: camera::Projection::Perspective(camera::Perspective::look_at(
+            scene::CAMERA,
+            Vec3::ZERO,
+            scene::UP,
+        )),
+        lights: vec![Light::directional(scene::LIGHT)],
+        environment: Environment::default(),
+        fog: None,
+        march: march::MarchSettings::default(),
+        // the materials used in the scene, indexed by their id
+        materials: vec![
+            // just a pink color
+            Material::new(vec3(0.5, 0.2, 0.5)),
+            // a dull grey
+            Material::new(vec3(0.1, 0.1, 0.1)),
+        ],
+        // our objects are static (don't move),
+        // so the scene is just their union
+        root: sphere.union(floor),
+    }
+}
+
+/// Renders and returns a color for each pixel for a given `width` and `height`, before post processing.
+///
+/// The colors of a debug view don't need any post processing.
+fn render<V, S>(
     // width of the image
     width: u32,
     // height of the image
     height: u32,
-    // ray origin
-    origin: <V as Comp<3>>::Vec,
-    // light direction for phong shading
-    ldir: <V as Comp<3>>::Vec,
-    // color of hit objects
-    mat: <V as Comp<3>>::Vec,
-    // the scene map,
-    // basically a fn(Vec3) -> V,
-    // needs to be Sync as its shared across threads
-    map: impl (Fn(<V as Comp<3>>::Vec) -> V) + Sync,
+    // how the samples of each pixel are taken
+    sampler: sampling::Sampler,
+    // only take all of the samples at the edges of objects
+    adaptive: Option<sampling::Adaptive>,
+    // how the scene is lit
+    lighting: Lighting,
+    // a false color view of how rays are marched, instead of the lit scene
+    debug: Option<debug::View>,
+    // the scene to render
+    scene: &Scene<S>,
 ) -> Vec<<V as Comp<3>>::Vec>
 where
+    // S is the objects in the scene:
+    // * knows the material of each object
+    // * needs to be Sync as its shared across threads
+    S: sdf::MaterialSdf<V> + Sync,
     // V:
     // * is a component of 2D and 3D vectors.
     // * can be used to produce a marching trace
+    // * can look up pixels of environment images
     // * generate positions of it's 2D vector
-    V: Comp<2> + Comp<3> + march::Traceable + Positions<Inner = <V as Comp<2>>::Vec>,
+    // * can be split into single pixels
+    V: Comp<2>
+        + Comp<3>
+        + march::Traceable
+        + environment::Texel
+        + Positions<Inner = <V as Comp<2>>::Vec>
+        + Lanes,
     // V's 2D vector implements Vector2D
     <V as Comp<2>>::Vec: vector::Vector2D<V>,
 {
+    // the scene map, basically a fn(Vec3) -> V
+    let map = |p| scene.root.dist(p);
     // output resolution
     let res = vec2(V::from(width as f32), V::from(height as f32));
+    let aspect = width as f32 / height as f32;
+
+    // shades the pixels at `pos`, returning their color before post processing,
+    // and the normal and hit mask of the first sample
+    let shade = |pos: <V as Comp<2>>::Vec, sampler: &sampling::Sampler| {
+        let mut col = <V as Comp<3>>::Vec::ZERO;
+        // total weight of all the samples
+        let mut total = V::ZERO;
+        // and all of the samples counting the same
+        let mut plain = <V as Comp<3>>::Vec::ZERO;
+        let mut first = None;
+        for i in 0..sampler.samples {
+            // offset from the middle of the pixel, following the sample pattern
+            let offset = sampler.offset::<V>(i, pos);
+            let weight = sampler.weight::<V>(offset);
+            let centre = broadcast::<2, V>(V::from(0.5));
 
-    V::positions(width, height)
-        .into_par_iter()
-        .map(|pos| {
             // RAY GENERATION
 
-            // calculate the "uv" coordiantes from the position on screen
-            let uv = ((pos * V::from(2.0)) - res) / -res.min_element();
-            // point the ray along the negative Z axis
-            let dir = vec3(uv.x(), uv.y(), V::from(-2.0));
-            let ray = Ray::new(origin, dir);
-
-            // RAY MARCHING
-
-            let Trace { distance, hit } = march::trace(&map, ray, None);
-            // position of where the ray hit
-            let pos = ray.at(distance);
-            // the surface normal
-            let nor = march::normal(&map, pos);
-
-            // LIGHTING
-
-            // amount of light in from phong shading
-            let lin: V = phong(ldir, nor, -ray.dir);
-            // light up the object in pink
-            let col = mat * lin;
-            // if not hit, the color should be black
-            let col = col * hit.select(V::ONE, V::ZERO);
-
-            // POST PROCESSING
+            // calculate the "uv" coordiantes from the position on screen,
+            // rows of pixels go down the screen but uv goes up
+            let [u, v] = ((pos + centre + offset) / res).to_array();
+            let uv = vec2(u, V::ONE - v);
+            // a different point on the lens for each sample
+            let lens = random::sample::<V>(sampler.seed(i), pos);
+            let ray: Ray<V> = scene.camera.ray(uv, lens, aspect);
+            // and a different point on lights with an area
+            let area = random::sample::<V>(sampler.seed(i), pos + broadcast::<2, V>(V::from(13.7)));
+
+            let (sample, primary) = match lighting.integrator {
+                path::Integrator::Whitted => {
+                    // rays left to trace, with the color they add to the sample
+                    // and which side of the surface each lane is on, `1` outside or `-1` inside
+                    let mut rays = vec![(ray, broadcast::<3, V>(V::ONE), V::ONE, 0)];
+                    let mut sample = <V as Comp<3>>::Vec::ZERO;
+                    // where the first ray ended, for the fog
+                    let mut primary = None;
+                    while let Some((ray, color, side, depth)) = rays.pop() {
+                        // RAY MARCHING
+
+                        // inside of an object the distance field is negative, so march it's negation
+                        let inner = |p| scene.root.dist(p) * side;
+                        // only rays from the camera start at the near plane
+                        let settings = match depth {
+                            0 => scene.march,
+                            _ => scene.march.secondary(),
+                        };
+                        let trace = march::trace(&inner, ray, &settings);
+                        let Trace { distance, hit, .. } = trace;
+                        let outside = side.simd_gt(V::ZERO);
+                        // position of where the ray hit
+                        let pos = ray.at(distance);
+                        // the surface normal, facing back towards the ray
+                        let nor = march::normal(&inner, pos, distance, &settings);
+                        // the material of the surface
+                        let (_, id) = scene.root.dist_mat(pos);
+                        let surface = material::surface(&scene.materials, id);
+                        // the normal and hit mask of the first ray are used by adaptive sampling
+                        if depth == 0 {
+                            first.get_or_insert((nor, hit.select(V::ONE, V::ZERO)));
+                            primary = Some(trace);
+                        }
+
+                        // LIGHTING
+
+                        // the light from every light that reaches the surface
+                        let lights = scene.lights.iter().map(|light| {
+                            let Incoming {
+                                dir,
+                                dist,
+                                radiance,
+                            } = light.incoming::<V>(pos, area);
+                            let shadow = match lighting.shadows {
+                                Some(shadows) => {
+                                    // start just above the surface so the shadow ray doesn't hit it
+                                    let origin = pos + nor * settings.offset(distance);
+                                    let ray = Ray::new(origin, dir);
+                                    march::shadow(&map, ray, dist, shadows, &settings)
+                                }
+                                None => V::ONE,
+                            };
+                            (dir, radiance * shadow)
+                        });
+                        // how much ambient light reaches the surface
+                        let occ = match lighting.occlusion {
+                            Some(ao) => march::occlusion(&map, pos, distance, nor, ao, &settings),
+                            None => V::ONE,
+                        };
+                        // and the light from the environment around it
+                        let ambient = scene.environment.ambient::<V>(nor);
+                        // light reflected towards the camera,
+                        // the inside of objects isn't lit and rays that miss see the environment
+                        let lit = material::shade(&surface, lights, nor, -ray.dir, ambient, occ);
+                        let zero = <V as Comp<3>>::Vec::ZERO;
+                        let background = scene.environment.radiance::<V>(ray.dir);
+                        sample += color
+                            * select::<3, V>(hit, select::<3, V>(outside, lit, zero), background);
+
+                        // REFLECTION AND REFRACTION
+
+                        if depth < lighting.bounces {
+                            let (reflected, refracted) =
+                                material::scatter(&surface, ray.dir, nor, outside);
+                            // reflected rays start just above the surface, refracted rays just below
+                            let offset = nor * settings.offset(distance);
+                            for (bounce, origin, side) in [
+                                (reflected, pos + offset, side),
+                                (refracted, pos - offset, -side),
+                            ] {
+                                let color = select::<3, V>(hit, color * bounce.color, zero);
+                                // only follow rays that still carry some light in any lane
+                                if color.max_element().simd_gt(V::ZERO).any() {
+                                    rays.push((
+                                        Ray::new(origin, bounce.dir),
+                                        color,
+                                        side,
+                                        depth + 1,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                    (sample, primary.unwrap())
+                }
+                path::Integrator::Path => {
+                    let mut rng = random::Rng::new(sampler.seed(i), pos);
+                    let shadows = lighting.shadows.is_some();
+                    let path = path::trace(scene, ray, &mut rng, lighting.bounces, shadows);
+                    first.get_or_insert((path.normal, path.hit));
+                    (path.color, path.trace)
+                }
+            };
+
+            // FOG
+
+            let sample = match &scene.fog {
+                Some(fog) => {
+                    let seed = pos + broadcast::<2, V>(V::from(27.1));
+                    let mut rng = random::Rng::new(sampler.seed(i), seed);
+                    fog.apply(scene, ray, primary, sample, &mut rng, lighting.shadows)
+                }
+                None => sample,
+            };
+            col += sample * weight;
+            total += weight;
+            plain += sample;
+        }
+        // the weighted average of all the samples,
+        // negative parts of the filter can push colors below zero,
+        // or leave no weight at all, where the plain average is used instead
+        let plain = plain / V::from(sampler.samples as f32);
+        let col = select::<3, V>(total.simd_gt(V::ZERO), col / total, plain);
+        let col = col.max(<V as Comp<3>>::Vec::ZERO);
+        let (nor, hit) = first.unwrap();
+        (col, nor, hit)
+    };
+
+    let positions = V::positions(width, height);
+
+    // DEBUG VIEWS
+
+    if let Some(view) = debug {
+        let cols: Vec<_> = positions
+            .par_iter()
+            .map(|&(pos, valid)| {
+                // a single ray through the middle of each pixel
+                let centre = broadcast::<2, V>(V::from(0.5));
+                let [u, v] = ((pos + centre) / res).to_array();
+                let ray: Ray<V> = scene.camera.ray(vec2(u, V::ONE - v), centre, aspect);
+                let trace = march::trace(&map, ray, &scene.march);
+                let pos = ray.at(trace.distance);
+                let nor = march::normal(&map, pos, trace.distance, &scene.march);
+                // every lane waits for the lane that took the most steps
+                let packet = (0..V::LANES)
+                    .map(|lane| V::scalar_lane(trace.steps, lane))
+                    .fold(0.0, f32::max);
+                let depth = scene.camera.depth(ray, trace.distance);
+                let col = debug::color(view, trace, nor, depth, V::from(packet), &scene.march);
+                // lanes past the end of the image aren't pixels
+                col * valid.select(V::ONE, V::ZERO)
+            })
+            .collect();
+        return match view {
+            debug::View::Depth => {
+                let furthest = cols
+                    .iter()
+                    .flat_map(|&col| (0..V::LANES).map(move |lane| V::lane(col, lane).x))
+                    .fold(0.0, f32::max);
+                cols.into_par_iter()
+                    .map(|col| debug::depth::<V>(col, furthest))
+                    .collect()
+            }
+            _ => cols,
+        };
+    }
 
-            // gain correction
-            let col =
-                (col * V::from(1.8)) / (V::ONE + col.dot(broadcast::<3, V>(V::from(1.0 / 3.0))));
-            // gamma correction
-            col.powf(V::from(1.0 / 2.2))
-        })
+    let cols: Vec<_> = match adaptive {
+        None => positions
+            .par_iter()
+            .map(|&(pos, _)| shade(pos, &sampler).0)
+            .collect(),
+        Some(adaptive) => {
+            // FIRST PASS
+
+            // a single sample in the middle of each pixel
+            let once = sampling::Sampler {
+                samples: 1,
+                pattern: sampling::Pattern::Grid,
+                filter: sampling::Filter::Box,
+                pass: sampler.pass,
+            };
+            let (mut cols, rest): (Vec<_>, Vec<_>) = positions
+                .par_iter()
+                .map(|&(pos, _)| {
+                    let (col, nor, hit) = shade(pos, &once);
+                    (col, (nor, hit))
+                })
+                .unzip();
+            let (nors, hits): (Vec<_>, Vec<_>) = rest.into_par_iter().unzip();
+
+            // FIND EDGES
+
+            let edges = adaptive.edges(width, height, |i| {
+                let (packet, lane) = (i as usize / V::LANES, i as usize % V::LANES);
+                sampling::Pixel {
+                    color: post::<f32>(V::lane(cols[packet], lane)),
+                    normal: V::lane(nors[packet], lane),
+                    hit: V::scalar_lane(hits[packet], lane) > 0.5,
+                }
+            });
+
+            // SECOND PASS
+
+            // pack the pixels on edges together, and take all of the samples
+            let refined: Vec<_> = V::gather(&edges, width)
+                .into_par_iter()
+                .map(|(pos, _)| shade(pos, &sampler).0)
+                .collect();
+            for (indices, col) in edges.chunks(V::LANES).zip(refined) {
+                for (lane, &i) in indices.iter().enumerate() {
+                    let (packet, to) = (i as usize / V::LANES, i as usize % V::LANES);
+                    V::set_lane(&mut cols[packet], to, V::lane(col, lane));
+                }
+            }
+            cols
+        }
+    };
+
+    cols.into_par_iter()
+        .zip(positions)
+        // lanes past the end of the image aren't pixels
+        .map(|(col, (_, valid))| col * valid.select(V::ONE, V::ZERO))
         .collect()
 }
 
-/// Phong shading.
-///
-/// https://en.wikipedia.org/wiki/Phong_shading
-fn phong<V: Comp<3>>(ldir: V::Vec, nor: V::Vec, eye: V::Vec) -> V {
-    // material settings
-    let ks = V::from(3.0); // specular
-    let kd = V::from(3.0); // diffuse
-    let ka = V::from(1.0); // ambient
-    let al = V::from(20.0); // shinyness
-
-    let rm = nor * nor.dot(ldir) * V::from(2.0) - ldir;
-    ka + (kd * ldir.dot(nor).clamp(V::ZERO, V::ONE)
-        + ks * rm.dot(eye).clamp(V::ZERO, V::ONE).powf(al))
+/// Settings for how the scene is lit.
+#[derive(Clone, Copy)]
+struct Lighting {
+    /// How shadows are cast, if at all.
+    shadows: Option<march::Shadows>,
+    /// How ambient light is blocked, if at all.
+    occlusion: Option<march::Occlusion>,
+    /// How many times rays can be reflected or refracted.
+    bounces: u32,
+    integrator: path::Integrator,
+}
+
+/// Post processing, applied to the final color of each pixel.
+fn post<V: Comp<3>>(col: V::Vec) -> V::Vec {
+    // gain correction
+    let col = (col * V::from(1.8)) / (V::ONE + col.dot(broadcast::<3, V>(V::from(1.0 / 3.0))));
+    // gamma correction
+    col.powf(V::from(1.0 / 2.2))
 }
 
 /// A type that is convertable to a stream of bytes to produce an image.
 trait ImageBytes {
-    fn bytes(self) -> Vec<u8>;
+    /// Only the bytes of the `width * height` pixels in the image are produced.
+    fn bytes(self, width: u32, height: u32) -> Vec<u8>;
 }
 
 /// converts a floating point value to a unsigned byte.
@@ -152,8 +623,8 @@ fn conv(x: f32) -> u8 {
 }
 
 impl ImageBytes for Vec<Vec3x8> {
-    fn bytes(self) -> Vec<u8> {
-        flatten(
+    fn bytes(self, width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = flatten(
             self.into_par_iter()
                 .map(|Vec3x8 { x, y, z }| {
                     // because a SIMD vector has multiple lanes, 24 values are produced.
@@ -167,12 +638,16 @@ impl ImageBytes for Vec<Vec3x8> {
                     arr
                 })
                 .collect(),
-        )
+        );
+        // the last packet may contain lanes past the end of the image
+        bytes.truncate((width * height) as usize * 3);
+        bytes
     }
 }
 
 impl ImageBytes for Vec<Vec3> {
-    fn bytes(self) -> Vec<u8> {
+    fn bytes(self, width: u32, height: u32) -> Vec<u8> {
+        debug_assert_eq!(self.len(), (width * height) as usize);
         flatten(
             self.into_par_iter()
                 .map(|Vec3 { x, y, z }| [conv(x), conv(y), conv(z)])
@@ -182,16 +657,22 @@ impl ImageBytes for Vec<Vec3> {
 }
 
 /// Generates screen space positions for all pixels in a given `width` and `height`
-trait Positions {
+trait Positions: Comp<2> {
     type Inner;
 
-    fn positions(width: u32, height: u32) -> Vec<Self::Inner>;
+    /// Each position comes with a mask of which lanes are pixels inside of the image.
+    fn positions(width: u32, height: u32) -> Vec<(Self::Inner, Self::Mask)>;
+
+    /// Positions of the pixels at `indices`, packed together in order.
+    ///
+    /// Lanes past the end of `indices` are masked off.
+    fn gather(indices: &[u32], width: u32) -> Vec<(Self::Inner, Self::Mask)>;
 }
 
 impl Positions for f32 {
     type Inner = Vec2;
 
-    fn positions(width: u32, height: u32) -> Vec<Self::Inner> {
+    fn positions(width: u32, height: u32) -> Vec<(Self::Inner, bool)> {
         let pixels = width * height;
         (0..pixels)
             // convert iterator into a parallel iterator
@@ -200,7 +681,17 @@ impl Positions for f32 {
             .map(|i| {
                 let x = (i % width) as f32;
                 let y = (i / width) as f32;
-                vec2(x, y)
+                (vec2(x, y), true)
+            })
+            .collect()
+    }
+    fn gather(indices: &[u32], width: u32) -> Vec<(Self::Inner, bool)> {
+        indices
+            .iter()
+            .map(|&i| {
+                let x = (i % width) as f32;
+                let y = (i / width) as f32;
+                (vec2(x, y), true)
             })
             .collect()
     }
@@ -209,7 +700,7 @@ impl Positions for f32 {
 impl Positions for f32x8 {
     type Inner = Vec2x8;
 
-    fn positions(width: u32, height: u32) -> Vec<Self::Inner> {
+    fn positions(width: u32, height: u32) -> Vec<(Self::Inner, mask32x8)> {
         let pixels = width * height;
         (0..pixels)
             // convert iterator into a parallel iterator
@@ -218,18 +709,96 @@ impl Positions for f32x8 {
             .step_by(f32x8::LANES)
             // calculate the position on screen
             .map(|i| {
-                const INC: f32x8 = f32x8::from_array([0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+                // the index of the pixel in each lane,
+                // a packet can wrap onto the next row if the width isn't a multiple of LANES
+                let lanes: [u32; f32x8::LANES] = core::array::from_fn(|lane| i + lane as u32);
                 // calculate cartesian coordiantes of the pixel
-                let x = (i % width) as f32;
-                let y = (i / width) as f32;
-                let y = f32x8::splat(y); // all Y values are the same
-                let x = f32x8::splat(x) + INC; // INC needs to be added so X is 8 different values
-                vec2(x, y)
+                let x = f32x8::from_array(lanes.map(|i| (i % width) as f32));
+                let y = f32x8::from_array(lanes.map(|i| (i / width) as f32));
+                // the last packet can hang off the end of the image
+                let valid = mask32x8::from_array(lanes.map(|i| i < pixels));
+                (vec2(x, y), valid)
+            })
+            .collect()
+    }
+    fn gather(indices: &[u32], width: u32) -> Vec<(Self::Inner, mask32x8)> {
+        indices
+            .chunks(f32x8::LANES)
+            .map(|chunk| {
+                // the last chunk is padded with copies of it's last pixel
+                let lanes: [u32; f32x8::LANES] =
+                    core::array::from_fn(|lane| chunk[lane.min(chunk.len() - 1)]);
+                let x = f32x8::from_array(lanes.map(|i| (i % width) as f32));
+                let y = f32x8::from_array(lanes.map(|i| (i / width) as f32));
+                let valid = mask32x8::from_array(core::array::from_fn(|lane| lane < chunk.len()));
+                (vec2(x, y), valid)
             })
             .collect()
     }
 }
 
+/// Splits packets of pixels into single pixels.
+///
+/// Pixel `i` of the image is in lane `i % LANES` of packet `i / LANES`.
+pub trait Lanes: Comp<3> {
+    /// The number of pixels in a packet.
+    const LANES: usize;
+
+    /// The pixel in `lane` of the packet `v`.
+    fn lane(v: Self::Vec, lane: usize) -> Vec3;
+
+    /// Replaces the pixel in `lane` of the packet `v`.
+    fn set_lane(v: &mut Self::Vec, lane: usize, pixel: Vec3);
+
+    /// The value in `lane` of `v`.
+    fn scalar_lane(v: Self, lane: usize) -> f32;
+
+    /// Why the ray in `lane` of the `trace` stopped.
+    fn reason(trace: &Trace<Self, Self::Mask>, lane: usize) -> march::Reason;
+}
+
+impl Lanes for f32 {
+    const LANES: usize = 1;
+
+    fn lane(v: Vec3, _lane: usize) -> Vec3 {
+        v
+    }
+
+    fn set_lane(v: &mut Vec3, _lane: usize, pixel: Vec3) {
+        *v = pixel;
+    }
+
+    fn scalar_lane(v: f32, _lane: usize) -> f32 {
+        v
+    }
+
+    fn reason(trace: &Trace<f32, bool>, _lane: usize) -> march::Reason {
+        trace.reason()
+    }
+}
+
+impl Lanes for f32x8 {
+    const LANES: usize = f32x8::LANES;
+
+    fn lane(v: Vec3x8, lane: usize) -> Vec3 {
+        vec3(v.x[lane], v.y[lane], v.z[lane])
+    }
+
+    fn set_lane(v: &mut Vec3x8, lane: usize, pixel: Vec3) {
+        v.x[lane] = pixel.x;
+        v.y[lane] = pixel.y;
+        v.z[lane] = pixel.z;
+    }
+
+    fn scalar_lane(v: f32x8, lane: usize) -> f32 {
+        v[lane]
+    }
+
+    fn reason(trace: &Trace<f32x8, mask32x8>, lane: usize) -> march::Reason {
+        trace.reason(lane)
+    }
+}
+
 /// Flattens a "Vector of array of T" into a "Vector of T".
 ///
 /// Much faster than calling [`Iterator::flatten`] for this specific case.
@@ -262,8 +831,3 @@ fn flatten<T, const N: usize>(v: Vec<[T; N]>) -> Vec<T> {
     // * capacity and length are adjusted accordingly
     unsafe { Vec::from_raw_parts(ptr.cast(), len * N, cap * N) }
 }
-
-/// Rounds `x` up to the nearest multiple of the value provided.
-const fn round_to_nearest<const M: u32>(x: u32) -> u32 {
-    (x + M - 1) & (u32::MAX - M + 1)
-}
@@ -3,66 +3,276 @@ use crate::vector::prelude::*;
 use crate::Vec3;
 
 /// The resulting trace of ray maching.
+#[derive(Clone, Copy)]
 pub struct Trace<D, H> {
     /// The distance to the surface.
     pub distance: D,
     /// Indicates if the surface was hit by the ray.
     pub hit: H,
+    /// Indicates if the ray went past the far plane without hitting anything.
+    ///
+    /// Rays that didn't hit anything and didn't go too far ran out of steps.
+    pub far: H,
+    /// How many steps the ray took.
+    pub steps: D,
+    /// The closest the ray got to the scene, for glows and outlines around objects.
+    pub closest: D,
+    /// How far along the ray it was [`Trace::closest`] to the scene.
+    pub closest_at: D,
 }
 
-/// The "precision" of all the algorithms in this module.
+/// Why the ray marcher stopped marching a ray, see [`Trace`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// The ray hit a surface.
+    Hit,
+    /// The ray went past the far plane.
+    Far,
+    /// The ray ran out of steps.
+    Steps,
+}
+
+impl Reason {
+    fn new(hit: bool, far: bool) -> Self {
+        match (hit, far) {
+            (true, _) => Reason::Hit,
+            (false, true) => Reason::Far,
+            (false, false) => Reason::Steps,
+        }
+    }
+}
+
+impl Trace<f32, bool> {
+    /// Why the ray stopped.
+    pub fn reason(&self) -> Reason {
+        Reason::new(self.hit, self.far)
+    }
+}
+
+impl Trace<f32x8, mask32x8> {
+    /// Why the ray in `lane` stopped.
+    pub fn reason(&self, lane: usize) -> Reason {
+        Reason::new(self.hit.test(lane), self.far.test(lane))
+    }
+}
+
+/// How close rays get to a surface to hit it, relative to how far they've gone,
+/// when it isn't set in [`MarchSettings`].
 pub const EPSILON: f32 = 0.001;
-/// The maximum number of steps that the ray marcher can take.
+/// The maximum number of steps that the ray marcher can take, when it isn't set in [`MarchSettings`].
 pub const MAX_STEPS: u32 = 64;
-/// The maximum distance the ray can travel.
-pub const MAX_DIST: f32 = f32::MAX;
+/// The maximum distance the ray can travel, when it isn't set in [`MarchSettings`].
+pub const MAX_DIST: f32 = 1000.0;
+/// How far shadow rays are marched towards a light.
+pub const SHADOW_DIST: f32 = 20.0;
+
+/// How close a ray has to get to a surface to hit it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Epsilon {
+    /// The same distance, however far the ray has gone.
+    Absolute(f32),
+    /// A fraction of how far the ray has gone, like the cone of a pixel growing with distance.
+    ///
+    /// Far away surfaces don't need as much detail as the ones right in front of the camera,
+    /// so they're hit in fewer steps.
+    Relative(f32),
+}
+
+impl Epsilon {
+    /// How close a ray that's gone `t` has to get to a surface.
+    #[inline]
+    pub fn at<V: Comp<3>>(self, t: V) -> V {
+        match self {
+            Epsilon::Absolute(e) => V::from(e),
+            Epsilon::Relative(e) => V::from(e) * t,
+        }
+    }
+
+    /// The same kind of epsilon, `scale` times as big.
+    #[must_use]
+    pub fn scaled(self, scale: f32) -> Self {
+        match self {
+            Epsilon::Absolute(e) => Epsilon::Absolute(e * scale),
+            Epsilon::Relative(e) => Epsilon::Relative(e * scale),
+        }
+    }
+}
+
+/// Settings for marching rays through the scene.
+///
+/// Large scenes want to see further, and scenes with tiny details want more steps and a smaller epsilon.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MarchSettings {
+    pub epsilon: Epsilon,
+    /// The maximum number of steps a ray can take.
+    pub max_steps: u32,
+    /// How far along the ray marching starts.
+    pub near: f32,
+    /// Rays that go further than this don't hit anything.
+    pub far: f32,
+    /// `w` in the enhanced sphere tracing algorithm, how much further than is safe each step tries to go.
+    ///
+    /// `0` is standard sphere tracing. When it isn't set `f32` rays use `0.87`,
+    /// and `f32x8` rays use standard sphere tracing, as keeping track of relaxed steps for every lane
+    /// costs more than the steps it saves.
+    pub relaxation: Option<f32>,
+}
+
+impl MarchSettings {
+    /// The same settings for rays that start on a surface, which don't skip the `near` part of the ray.
+    #[must_use]
+    pub fn secondary(self) -> Self {
+        Self { near: 0.0, ..self }
+    }
+
+    /// How far off of a surface that was hit after going `t` the rays leaving it start,
+    /// so they're outside of the distance they would hit it from.
+    #[inline]
+    pub fn offset<V: Comp<3>>(&self, t: V) -> V {
+        // surfaces right next to the eye would have no distance at all
+        self.epsilon.at(t).max(V::from(EPSILON * EPSILON)) * V::from(2.0)
+    }
+}
+
+impl Default for MarchSettings {
+    fn default() -> Self {
+        Self {
+            epsilon: Epsilon::Relative(EPSILON),
+            max_steps: MAX_STEPS,
+            near: 0.0,
+            far: MAX_DIST,
+            relaxation: None,
+        }
+    }
+}
+
+/// How the edges of shadows are softened, see [`shadow`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Penumbra {
+    /// No softening, a point is either in shadow or it isn't.
+    Hard,
+    /// The classic estimate `k * h / t`,
+    /// from how close the shadow ray gets to the scene compared to how far it's travelled.
+    Classic,
+    /// Finds the closest point between the last two steps,
+    /// which stops the banding of the classic estimate around sharp corners.
+    #[default]
+    Improved,
+}
+
+/// Settings for the shadows cast by lights.
+#[derive(Clone, Copy, Debug)]
+pub struct Shadows {
+    pub penumbra: Penumbra,
+    /// `k` in the penumbra estimate, higher is sharper.
+    pub softness: f32,
+}
+
+impl Default for Shadows {
+    fn default() -> Self {
+        Self {
+            penumbra: Penumbra::default(),
+            softness: 8.0,
+        }
+    }
+}
 
 /// Returns a [`Trace`] of the ray marching algorithm.
 ///
 /// # Parameters
-/// * `map`:    For a given point, returns the maximum distance the marcher can step.
-/// * `ray`:    The ray to march, will march from it's origin along it's direction.
-/// * `w`:      Step reduction size, can be modified on a per scene basis for improved performance.
-///             Default value is `0.87`.
+/// * `map`:      For a given point, returns the maximum distance the marcher can step.
+/// * `ray`:      The ray to march, will march from it's origin along it's direction.
+/// * `settings`: How far the ray goes and how precise it is, see [`MarchSettings`].
+///
+/// Rays that don't hit anything stop where the marcher gave up on them.
+#[inline]
+pub fn trace<S, V>(map: &S, ray: Ray<V>, settings: &MarchSettings) -> Trace<V, V::Mask>
+where
+    V: Traceable,
+    S: Fn(V::Vec) -> V,
+{
+    Traceable::trace(map, ray, settings)
+}
+
+/// Returns how much light reaches the origin of the `ray` from a light `far` away in it's direction,
+/// from `0` in full shadow to `1` fully lit.
+///
+/// Shadow rays don't go further than [`SHADOW_DIST`], and hit the scene like any other ray with the `settings`.
+/// The origin should be moved off of the surface by [`MarchSettings::offset`],
+/// so the ray doesn't hit it straight away.
+///
+/// https://iquilezles.org/articles/rmshadows/
 #[inline]
-pub fn trace<S, V>(map: &S, ray: Ray<V>, w: Option<V>) -> Trace<V, V::Mask>
+pub fn shadow<S, V>(map: &S, ray: Ray<V>, far: V, shadows: Shadows, settings: &MarchSettings) -> V
 where
     V: Traceable,
     S: Fn(V::Vec) -> V,
 {
-    Traceable::trace(map, ray, w)
+    Traceable::shadow(map, ray, far, shadows, settings)
+}
+
+/// How much light gets past the scene, after a step of distance `h` at `t` along a shadow ray.
+///
+/// `ph` is the distance of the previous step.
+#[inline]
+fn penumbra<V: Comp<3>>(shadows: Shadows, h: V, ph: V, t: V) -> V {
+    let k = V::from(shadows.softness);
+    match shadows.penumbra {
+        Penumbra::Hard => V::ONE,
+        Penumbra::Classic => k * h / t,
+        Penumbra::Improved => {
+            // the spheres of the last two steps overlap,
+            // `y` is how far back along the ray they're closest to the scene, and `d` how close
+            let y = h * h / (V::from(2.0) * ph);
+            let d = (h * h - y * y).max(V::ZERO).sqrt();
+            k * d / (t - y).max(V::from(EPSILON))
+        }
+    }
 }
 
 /// A type that can be used to march a map.
 pub trait Traceable: Comp<3> {
-    type Mask: Maskable<Self>;
+    #[doc(hidden)]
+    fn trace<S>(map: &S, ray: Ray<Self>, settings: &MarchSettings) -> Trace<Self, Self::Mask>
+    where
+        S: Fn(Self::Vec) -> Self;
 
     #[doc(hidden)]
-    fn trace<S>(map: &S, ray: Ray<Self>, w: Option<Self>) -> Trace<Self, Self::Mask>
+    fn shadow<S>(
+        map: &S,
+        ray: Ray<Self>,
+        far: Self,
+        shadows: Shadows,
+        settings: &MarchSettings,
+    ) -> Self
     where
         S: Fn(Self::Vec) -> Self;
 }
 
 impl Traceable for f32 {
-    type Mask = bool;
-
     #[inline]
-    fn trace<S>(map: &S, ray: Ray<f32>, w: Option<f32>) -> Trace<f32, bool>
+    fn trace<S>(map: &S, ray: Ray<f32>, settings: &MarchSettings) -> Trace<f32, bool>
     where
         S: Fn(Vec3) -> f32,
     {
         // Implemented using the enhanced sphere tracing algorithm outlined in the paper below.
         // https://diglib.eg.org/bitstream/handle/10.2312/egs20181037/029-032.pdf
-        let w = w.unwrap_or(0.87);
+        let w = settings.relaxation.unwrap_or(0.87);
+
+        let mut t = settings.near; // total distance
 
         let mut rp = 0.0; // prev
-        let mut rc = 1.0; // curr
+        let mut rc = map(ray.at(t)); // curr, rays can start right next to a surface
         let mut rn; // next
 
         let mut di = 0.0; // overstep distance
-        let mut t = 0.0; // total distance
 
-        for _ in 0..MAX_STEPS {
+        let mut steps = 0;
+        let (mut closest, mut closest_at) = (rc, t);
+        let (mut hit, mut far) = (false, false);
+
+        while steps < settings.max_steps {
+            steps += 1;
             di = rc + w * rc * ((di - rp + rc) / (di + rp - rc)).max(0.6);
             rn = map(ray.at(t + di));
             if di > rc + rn {
@@ -71,70 +281,247 @@ impl Traceable for f32 {
                 rn = map(ray.at(t + di));
             }
             t += di;
+            if rn < closest {
+                (closest, closest_at) = (rn, t);
+            }
             // (f < (t) * cam_pixel_growth*0.01)
-            if rn < t * EPSILON {
-                // hit
-                return Trace {
-                    distance: t,
-                    hit: true,
-                };
+            if rn < settings.epsilon.at(t) {
+                hit = true;
+                break;
+            }
+            if t > settings.far {
+                far = true;
+                break;
             }
             rp = rc;
             rc = rn;
         }
 
         Trace {
-            distance: MAX_DIST,
-            hit: false,
+            distance: t,
+            hit,
+            far,
+            steps: steps as f32,
+            closest,
+            closest_at,
+        }
+    }
+
+    #[inline]
+    fn shadow<S>(
+        map: &S,
+        ray: Ray<f32>,
+        far: f32,
+        shadows: Shadows,
+        settings: &MarchSettings,
+    ) -> f32
+    where
+        S: Fn(Vec3) -> f32,
+    {
+        let mut res = 1.0_f32;
+        let mut ph = 1e20; // previous step
+        let mut t = 0.0;
+        let far = far.min(SHADOW_DIST);
+
+        for _ in 0..settings.max_steps {
+            let h = map(ray.at(t));
+            if h < settings.epsilon.at(t) {
+                // hit something on the way to the light
+                return 0.0;
+            }
+            res = res.min(penumbra(shadows, h, ph, t));
+            ph = h;
+            t += h;
+            if t > far {
+                break;
+            }
         }
+        res.clamp(0.0, 1.0)
     }
 }
 
-use std::simd::{f32x8, mask32x8, SimdPartialOrd};
+use std::simd::{f32x8, mask32x8, SimdFloat, SimdPartialOrd};
 
 impl Traceable for f32x8 {
-    type Mask = mask32x8;
+    #[inline]
+    fn trace<S>(map: &S, ray: Ray<f32x8>, settings: &MarchSettings) -> Trace<f32x8, mask32x8>
+    where
+        S: Fn(Vec3x8) -> f32x8,
+    {
+        // the relaxed loop has more to keep track of for every lane,
+        // which costs more than the steps it saves in most scenes
+        match settings.relaxation {
+            Some(w) if w > 0.0 => relaxed_x8(map, ray, settings, w),
+            _ => standard_x8(map, ray, settings),
+        }
+    }
 
     #[inline]
-    fn trace<S>(map: &S, ray: Ray<f32x8>, _w: Option<f32x8>) -> Trace<f32x8, mask32x8>
+    fn shadow<S>(
+        map: &S,
+        ray: Ray<f32x8>,
+        far: f32x8,
+        shadows: Shadows,
+        settings: &MarchSettings,
+    ) -> f32x8
     where
         S: Fn(Vec3x8) -> f32x8,
     {
-        // use a much simpler version of the ray marching algorithm
-        // if the enhanced version above was translated into "simd friendly" code
-        // the 'map' function would have to be called twice as no individual branches can be made
-        let mut t = f32x8::splat(0.0);
-        let mut hit = mask32x8::splat(false);
-        for _ in 0..MAX_STEPS {
-            // see how far we need to step
+        let zero = f32x8::splat(0.0);
+        let mut res = f32x8::splat(1.0);
+        let mut ph = f32x8::splat(1e20);
+        let mut t = zero;
+        let mut blocked = mask32x8::splat(false);
+        let mut finished = mask32x8::splat(false);
+        let far = far.simd_min(f32x8::splat(SHADOW_DIST));
+
+        for _ in 0..settings.max_steps {
             let h = map(ray.at(t));
-            // we hit something if the step distance was small
-            hit = h.simd_lt(f32x8::splat(EPSILON) * t);
-            // rays are finished if they hit something or went too far
-            let finished = hit | t.simd_gt(f32x8::splat(MAX_DIST));
-            // exit when all rays in the wave are finished
+            // rays that hit something on the way to the light are in full shadow
+            blocked |= !finished & h.simd_lt(settings.epsilon.at(t));
+            finished |= blocked;
+            // only unfinished rays get any closer to the scene
+            let p = penumbra(shadows, h, ph, t);
+            res = finished.select(res, res.simd_min(p));
+            ph = finished.select(ph, h);
+            t += finished.select(zero, h);
+            finished |= t.simd_gt(far);
             if finished.all() {
                 break;
             }
-            // add the step dist to unfinished rays
-            t += finished.select(f32x8::splat(0.0), h);
         }
-        Trace { distance: t, hit }
+        blocked.select(zero, res.simd_clamp(zero, f32x8::splat(1.0)))
+    }
+}
+
+/// Standard sphere tracing of 8 rays at once, stepping each ray by the distance to the scene.
+#[inline]
+fn standard_x8<S>(map: &S, ray: Ray<f32x8>, settings: &MarchSettings) -> Trace<f32x8, mask32x8>
+where
+    S: Fn(Vec3x8) -> f32x8,
+{
+    let mut t = f32x8::splat(settings.near); // total distance
+
+    let mut hit = mask32x8::splat(false);
+    let mut far = mask32x8::splat(false);
+
+    let mut steps = f32x8::splat(0.0);
+    let (mut closest, mut closest_at) = (f32x8::splat(f32::MAX), t);
+
+    // finished rays stay where they are, so all of this stays the same for them
+    for _ in 0..settings.max_steps {
+        // see how far we need to step
+        let h = map(ray.at(t));
+        let closer = h.simd_lt(closest);
+        closest = closer.select(h, closest);
+        closest_at = closer.select(t, closest_at);
+        // we hit something if the step distance was small
+        hit = h.simd_lt(settings.epsilon.at(t));
+        // rays are finished if they hit something or went too far
+        far = !hit & t.simd_gt(f32x8::splat(settings.far));
+        let finished = hit | far;
+        // exit when all rays in the wave are finished
+        if finished.all() {
+            break;
+        }
+        // add the step dist to unfinished rays
+        steps += finished.select(f32x8::splat(0.0), f32x8::splat(1.0));
+        t += finished.select(f32x8::splat(0.0), h);
+    }
+    Trace {
+        distance: t,
+        hit,
+        far,
+        steps,
+        closest,
+        closest_at,
+    }
+}
+
+/// The enhanced sphere tracing algorithm of [`Traceable::trace`] for `f32`, for 8 rays at once with relaxation `w`.
+#[inline]
+fn relaxed_x8<S>(
+    map: &S,
+    ray: Ray<f32x8>,
+    settings: &MarchSettings,
+    w: f32,
+) -> Trace<f32x8, mask32x8>
+where
+    S: Fn(Vec3x8) -> f32x8,
+{
+    // The same enhanced sphere tracing algorithm as the scalar version above.
+    // When a step goes too far, the scalar version falls back by calling `map` again straight away,
+    // which would have to be done for every lane here as no individual branches can be made.
+    // Instead, those lanes stay where they are and take the standard step on the next iteration.
+    let w = f32x8::splat(w);
+
+    let mut t = f32x8::splat(settings.near); // total distance
+
+    let mut rp = f32x8::splat(0.0); // prev
+    let mut rc = map(ray.at(t)); // curr, rays can start right next to a surface
+
+    let mut di = f32x8::splat(0.0); // overstep distance
+
+    // lanes that went too far last time, which take a standard step
+    let mut fallback = mask32x8::splat(false);
+    let mut hit = mask32x8::splat(false);
+    let mut far = mask32x8::splat(false);
+    let mut finished = mask32x8::splat(false);
+
+    let mut steps = f32x8::splat(0.0);
+    let (mut closest, mut closest_at) = (rc, t);
+
+    for _ in 0..settings.max_steps {
+        // only count the steps of rays that are still being marched
+        steps = finished.select(steps, steps + f32x8::splat(1.0));
+        let relaxed = rc + w * rc * ((di - rp + rc) / (di + rp - rc)).simd_max(f32x8::splat(0.6));
+        let step = fallback.select(rc, relaxed);
+        let rn = map(ray.at(t + step));
+        // the spheres of the two steps don't overlap, so a surface could have been skipped
+        fallback = !fallback & step.simd_gt(rc + rn);
+        // every other lane that isn't finished moves forwards
+        let moved = !finished & !fallback;
+        t = moved.select(t + step, t);
+        di = moved.select(step, di);
+        rp = moved.select(rc, rp);
+        rc = moved.select(rn, rc);
+        let closer = moved & rn.simd_lt(closest);
+        closest = closer.select(rn, closest);
+        closest_at = closer.select(t, closest_at);
+        // (f < (t) * cam_pixel_growth*0.01)
+        hit |= moved & rn.simd_lt(settings.epsilon.at(t));
+        // rays are finished if they hit something or went too far
+        far |= !finished & !hit & t.simd_gt(f32x8::splat(settings.far));
+        finished |= hit | far;
+        // exit when all rays in the wave are finished
+        if finished.all() {
+            break;
+        }
+    }
+    Trace {
+        distance: t,
+        hit,
+        far,
+        steps,
+        closest,
+        closest_at,
     }
 }
 
-/// Calculates the surface normal at point `p`.
+/// Calculates the surface normal at point `p`, which a ray reached after going `t`.
 ///
 /// The normal is only correct when `p` is on,
 /// or very close to, a surface for the given `map`.
-pub fn normal<V: Comp<3>, S>(map: &S, p: V::Vec) -> V::Vec
+/// It's found over the same distance that the ray hit the surface from, see [`MarchSettings::epsilon`].
+pub fn normal<V: Comp<3>, S>(map: &S, p: V::Vec, t: V, settings: &MarchSettings) -> V::Vec
 where
     S: Fn(V::Vec) -> V,
 {
     // https://iquilezles.org/articles/normalsSDF/
     let x = V::from(1.0);
     let y = V::from(-1.0);
-    let ep = V::from(EPSILON);
+    // points right next to the eye would have no distance between the samples
+    let ep = settings.epsilon.at(t).max(V::from(EPSILON * EPSILON));
 
     let xyy = vec3(x, y, y);
     let yyx = vec3(y, y, x);
@@ -146,3 +533,108 @@ where
         + xxx * map(p + xxx * ep))
     .normalise()
 }
+
+/// How many points are sampled along each direction for ambient occlusion.
+pub const AO_STEPS: u32 = 5;
+
+/// Which directions ambient occlusion is sampled in, see [`occlusion`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OcclusionMode {
+    /// Only straight out along the surface normal, the cheapest.
+    #[default]
+    Normal,
+    /// Spread over the hemisphere around the normal,
+    /// which also finds objects that are off to the side.
+    Hemisphere,
+}
+
+/// Settings for ambient occlusion.
+#[derive(Clone, Copy, Debug)]
+pub struct Occlusion {
+    pub mode: OcclusionMode,
+    /// How far away from the surface the scene is sampled.
+    pub distance: f32,
+    /// How many directions are sampled by [`OcclusionMode::Hemisphere`].
+    pub directions: u32,
+}
+
+impl Default for Occlusion {
+    fn default() -> Self {
+        Self {
+            mode: OcclusionMode::default(),
+            distance: 0.12,
+            directions: 8,
+        }
+    }
+}
+
+/// Calculates how much ambient light reaches the point `p` with surface normal `nor`,
+/// from `0` fully blocked to `1` unblocked.
+///
+/// Points along a direction are closer to the scene than their distance from `p`
+/// when something else is nearby, and the difference is how much light is blocked.
+///
+/// The first points are [`MarchSettings::offset`] away from `p`, which a ray reached after going `t`.
+///
+/// https://iquilezles.org/articles/nvscene2008/rwwtt.pdf
+pub fn occlusion<V: Comp<3>, S>(
+    map: &S,
+    p: V::Vec,
+    t: V,
+    nor: V::Vec,
+    ao: Occlusion,
+    settings: &MarchSettings,
+) -> V
+where
+    S: Fn(V::Vec) -> V,
+{
+    let start = settings.offset(t);
+    match ao.mode {
+        OcclusionMode::Normal => occlusion_along(map, p, nor, V::ONE, start, ao.distance),
+        OcclusionMode::Hemisphere => {
+            let (tangent, bitangent) = basis::<V>(nor);
+
+            let n = ao.directions.max(1);
+            let golden = core::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+            let total = (0..n).fold(V::ZERO, |total, i| {
+                // cosine weighted directions, spiralling out from the normal
+                let s = (i as f32 + 0.5) / n as f32;
+                let (sin, cos) = (i as f32 * golden).sin_cos();
+                let (r, height) = (s.sqrt(), (1.0 - s).sqrt());
+                let dir = tangent * V::from(r * cos)
+                    + bitangent * V::from(r * sin)
+                    + nor * V::from(height);
+                total + occlusion_along(map, p, dir, V::from(height), start, ao.distance)
+            });
+            total / V::from(n as f32)
+        }
+    }
+}
+
+/// Ambient occlusion from [`AO_STEPS`] points from `start` up to `distance` along `dir`.
+///
+/// `height` is how far above the surface each unit along `dir` goes,
+/// the cosine of the angle between `dir` and the normal.
+fn occlusion_along<V: Comp<3>, S>(
+    map: &S,
+    p: V::Vec,
+    dir: V::Vec,
+    height: V,
+    start: V,
+    distance: f32,
+) -> V
+where
+    S: Fn(V::Vec) -> V,
+{
+    let mut occ = V::ZERO;
+    // points further away count less
+    let mut scale = V::ONE;
+    for i in 0..AO_STEPS {
+        let h = start + V::from(distance * i as f32 / (AO_STEPS - 1) as f32);
+        let d = map(p + dir * h);
+        // with nothing else nearby the point would be `h * height` from the surface
+        occ += (h * height - d) * scale;
+        scale *= V::from(0.95);
+    }
+    (V::ONE - V::from(3.0) * occ).clamp(V::ZERO, V::ONE)
+}
@@ -0,0 +1,326 @@
+use core::f32::consts::PI;
+
+use crate::vector::prelude::*;
+
+/// How light is reflected off of a surface.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Model {
+    /// The classic Phong model, quick but not physically based.
+    ///
+    /// https://en.wikipedia.org/wiki/Phong_shading
+    #[default]
+    Phong,
+    /// The Cook-Torrance microfacet model, with the GGX distribution,
+    /// Smith's shadowing and Schlick's approximation of the Fresnel effect.
+    ///
+    /// https://learnopengl.com/PBR/Theory
+    CookTorrance,
+}
+
+/// Describes how the surface of an object is shaded.
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    /// The color of the surface, it's albedo.
+    pub color: Vec3,
+    pub model: Model,
+    /// How much the surface acts like a metal, from `0` to `1`.
+    ///
+    /// Only used by [`Model::CookTorrance`].
+    pub metallic: f32,
+    /// How rough the surface is, from `0` for a mirror to `1`.
+    ///
+    /// Only used by [`Model::CookTorrance`].
+    pub roughness: f32,
+    /// Light given off by the surface itself.
+    pub emissive: Vec3,
+    /// How much of the surface is a mirror, from `0` to `1`.
+    ///
+    /// Metals tint their reflections with their color.
+    pub reflectivity: f32,
+    /// How much light passes through the surface, from `0` to `1`, like glass.
+    ///
+    /// Some light is still reflected, more at glancing angles.
+    pub transmission: f32,
+    /// The index of refraction, how much light bends passing through the surface.
+    pub ior: f32,
+}
+
+impl Material {
+    /// The roughness of new materials.
+    pub const ROUGHNESS: f32 = 0.5;
+    /// The index of refraction of new materials, about the same as glass.
+    pub const IOR: f32 = 1.5;
+
+    /// A [`Model::Phong`] material with the given `color`.
+    pub const fn new(color: Vec3) -> Self {
+        Self {
+            color,
+            model: Model::Phong,
+            metallic: 0.0,
+            roughness: Self::ROUGHNESS,
+            emissive: Vec3::ZERO,
+            reflectivity: 0.0,
+            transmission: 0.0,
+            ior: Self::IOR,
+        }
+    }
+
+    /// Sets how light is reflected off of the surface.
+    #[must_use]
+    pub fn with_model(self, model: Model) -> Self {
+        Self { model, ..self }
+    }
+
+    /// Sets how much the surface acts like a metal.
+    #[must_use]
+    pub fn with_metallic(self, metallic: f32) -> Self {
+        Self { metallic, ..self }
+    }
+
+    /// Sets how rough the surface is.
+    #[must_use]
+    pub fn with_roughness(self, roughness: f32) -> Self {
+        Self { roughness, ..self }
+    }
+
+    /// Sets the light given off by the surface.
+    #[must_use]
+    pub fn with_emissive(self, emissive: Vec3) -> Self {
+        Self { emissive, ..self }
+    }
+
+    /// Sets how much of the surface is a mirror.
+    #[must_use]
+    pub fn with_reflectivity(self, reflectivity: f32) -> Self {
+        Self {
+            reflectivity,
+            ..self
+        }
+    }
+
+    /// Sets how much light passes through the surface, and how much it bends.
+    #[must_use]
+    pub fn with_transmission(self, transmission: f32, ior: f32) -> Self {
+        Self {
+            transmission,
+            ior,
+            ..self
+        }
+    }
+}
+
+/// The material of a surface, with a different material in each lane.
+pub struct Surface<V: Comp<3>> {
+    pub color: V::Vec,
+    /// Set for lanes using [`Model::CookTorrance`].
+    pub pbr: V::Mask,
+    pub metallic: V,
+    pub roughness: V,
+    pub emissive: V::Vec,
+    pub reflectivity: V,
+    pub transmission: V,
+    pub ior: V,
+}
+
+/// Looks up the material with the given `id`, for every lane.
+///
+/// Unknown ids are a black [`Model::Phong`] material.
+pub fn surface<V: Comp<3>>(materials: &[Material], id: V) -> Surface<V> {
+    let none = Surface {
+        color: V::Vec::ZERO,
+        pbr: V::ZERO.simd_gt(V::ZERO),
+        metallic: V::ZERO,
+        roughness: V::from(Material::ROUGHNESS),
+        emissive: V::Vec::ZERO,
+        reflectivity: V::ZERO,
+        transmission: V::ZERO,
+        ior: V::from(Material::IOR),
+    };
+    materials.iter().enumerate().fold(none, |s, (i, mat)| {
+        let is = id.simd_eq(V::from(i as f32));
+        Surface {
+            color: select::<3, V>(is, from_vec3::<V>(mat.color), s.color),
+            pbr: match mat.model {
+                Model::CookTorrance => is | s.pbr,
+                Model::Phong => !is & s.pbr,
+            },
+            metallic: is.select(V::from(mat.metallic), s.metallic),
+            roughness: is.select(V::from(mat.roughness), s.roughness),
+            emissive: select::<3, V>(is, from_vec3::<V>(mat.emissive), s.emissive),
+            reflectivity: is.select(V::from(mat.reflectivity), s.reflectivity),
+            transmission: is.select(V::from(mat.transmission), s.transmission),
+            ior: is.select(V::from(mat.ior), s.ior),
+        }
+    })
+}
+
+/// The color of the `surface` with normal `nor`, seen from the direction `eye`,
+/// lit by the direction towards and the color of every light in `lights`.
+///
+/// `ambient` is the light from the environment reaching the surface,
+/// and `occlusion` is how much of the ambient light isn't blocked.
+pub fn shade<V: Comp<3>>(
+    surface: &Surface<V>,
+    lights: impl Iterator<Item = (V::Vec, V::Vec)>,
+    nor: V::Vec,
+    eye: V::Vec,
+    ambient: V::Vec,
+    occlusion: V,
+) -> V::Vec {
+    let ambient = ambient * occlusion;
+    // both models are worked out, as each lane can use a different one
+    let (phong, pbr) = lights.fold(
+        (
+            broadcast::<3, V>(V::from(PHONG_AMBIENT) * occlusion) + ambient,
+            surface.color * (broadcast::<3, V>(V::from(PBR_AMBIENT) * occlusion) + ambient),
+        ),
+        |(phong, pbr), (ldir, color)| {
+            (
+                phong + self::phong::<V>(ldir, color, nor, eye),
+                pbr + cook_torrance::<V>(surface, ldir, color, nor, eye),
+            )
+        },
+    );
+    select::<3, V>(surface.pbr, pbr, surface.color * phong) * direct(surface) + surface.emissive
+}
+
+/// Light reflected by the `surface` towards `eye`, from a light of `color` in the direction `ldir`.
+///
+/// This is the BRDF multiplied by the cosine of the angle to the light,
+/// [`Model::Phong`] surfaces are Lambertian as Phong shading doesn't conserve energy.
+pub fn brdf<V: Comp<3>>(
+    surface: &Surface<V>,
+    ldir: V::Vec,
+    color: V::Vec,
+    nor: V::Vec,
+    eye: V::Vec,
+) -> V::Vec {
+    let lambert = surface.color * color * (nor.dot(ldir).max(V::ZERO) / V::from(PI));
+    let pbr = cook_torrance::<V>(surface, ldir, color, nor, eye);
+    select::<3, V>(surface.pbr, pbr, lambert) * direct(surface)
+}
+
+/// Ambient light added to [`Model::Phong`] surfaces, before the color.
+const PHONG_AMBIENT: f32 = 1.0;
+/// Ambient light added to [`Model::CookTorrance`] surfaces.
+const PBR_AMBIENT: f32 = 0.03;
+
+/// Light reflected by Phong shading from a light of `color` in the direction `ldir`,
+/// before being multiplied by the color of the surface.
+#[inline]
+fn phong<V: Comp<3>>(ldir: V::Vec, color: V::Vec, nor: V::Vec, eye: V::Vec) -> V::Vec {
+    // material settings
+    let ks = V::from(3.0); // specular
+    let kd = V::from(3.0); // diffuse
+    let al = V::from(20.0); // shinyness
+
+    let rm = nor * nor.dot(ldir) * V::from(2.0) - ldir;
+    color
+        * (kd * ldir.dot(nor).clamp(V::ZERO, V::ONE)
+            + ks * rm.dot(eye).clamp(V::ZERO, V::ONE).powf(al))
+}
+
+/// Light reflected by the Cook-Torrance model from a light of `color` in the direction `ldir`.
+#[inline]
+fn cook_torrance<V: Comp<3>>(
+    surface: &Surface<V>,
+    ldir: V::Vec,
+    color: V::Vec,
+    nor: V::Vec,
+    eye: V::Vec,
+) -> V::Vec {
+    let one = V::ONE;
+    let half = (ldir + eye).normalise();
+    let n_l = nor.dot(ldir).max(V::ZERO);
+    let n_v = nor.dot(eye).max(V::ZERO);
+    let n_h = nor.dot(half).max(V::ZERO);
+    let v_h = eye.dot(half).max(V::ZERO);
+
+    // very smooth surfaces make the highlight infinitely small
+    let roughness = surface.roughness.clamp(V::from(0.02), one);
+
+    // GGX normal distribution, how many microfacets face `half`
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let d = n_h * n_h * (a2 - one) + one;
+    let distribution = a2 / (V::from(PI) * d * d);
+
+    // Smith's method with Schlick-GGX, how many microfacets are hidden
+    let k = (roughness + one) * (roughness + one) / V::from(8.0);
+    let g1 = |x: V| x / (x * (one - k) + k);
+    let geometry = g1(n_v) * g1(n_l);
+
+    // Schlick's Fresnel, metals tint their reflections
+    let f0 = broadcast::<3, V>(V::from(0.04)) * (one - surface.metallic)
+        + surface.color * surface.metallic;
+    let fresnel = f0 + (broadcast::<3, V>(one) - f0) * (one - v_h).powf(V::from(5.0));
+
+    let specular = fresnel * (distribution * geometry / (V::from(4.0) * n_v * n_l + V::from(1e-4)));
+    // light that isn't reflected is diffused, except by metals
+    let diffuse =
+        (broadcast::<3, V>(one) - fresnel) * (one - surface.metallic) * surface.color / V::from(PI);
+
+    (diffuse + specular) * color * n_l
+}
+
+/// A ray leaving a surface after being reflected or refracted.
+pub struct Bounce<V: Comp<3>> {
+    pub dir: V::Vec,
+    /// How much of the light along the ray makes it back.
+    pub color: V::Vec,
+}
+
+/// The rays reflected off of, and refracted through,
+/// the `surface` with the facing normal `nor` by a ray going in the direction `dir`.
+///
+/// `outside` is set for lanes where `dir` is outside of the object.
+/// The color of the refracted ray is zero if all of the light is reflected.
+pub fn scatter<V: Comp<3>>(
+    surface: &Surface<V>,
+    dir: V::Vec,
+    nor: V::Vec,
+    outside: V::Mask,
+) -> (Bounce<V>, Bounce<V>) {
+    let one = V::ONE;
+    let white = broadcast::<3, V>(one);
+    let cos_i = -dir.dot(nor);
+    let reflected = dir + nor * (cos_i * V::from(2.0));
+
+    // Snell's law, going into the object light bends towards the normal
+    let eta = outside.select(one / surface.ior, surface.ior);
+    let k = one - eta * eta * (one - cos_i * cos_i);
+    // past the critical angle, all of the light is reflected
+    let total = k.simd_lt(V::ZERO);
+    let cos_t = k.max(V::ZERO).sqrt();
+    let refracted = dir * eta + nor * (eta * cos_i - cos_t);
+
+    // Schlick's approximation, with the angle on the less dense side
+    let r0 = (one - surface.ior) / (one + surface.ior);
+    let r0 = r0 * r0;
+    let cos = outside.select(cos_i, cos_t);
+    let fresnel = total.select(
+        one,
+        r0 + (one - r0) * (one - cos).max(V::ZERO).powf(V::from(5.0)),
+    );
+
+    // metals tint their reflections
+    let tint = white * (one - surface.metallic) + surface.color * surface.metallic;
+    let reflect = tint * surface.reflectivity + white * (surface.transmission * fresnel);
+    // light passing through is tinted by the color of the object
+    let refract = surface.color * (surface.transmission * (one - fresnel));
+    (
+        Bounce {
+            dir: reflected,
+            color: reflect,
+        },
+        Bounce {
+            dir: refracted,
+            color: refract,
+        },
+    )
+}
+
+/// How much of the `surface` is lit directly, rather than reflecting or passing on light.
+pub fn direct<V: Comp<3>>(surface: &Surface<V>) -> V {
+    (V::ONE - surface.reflectivity - surface.transmission).max(V::ZERO)
+}
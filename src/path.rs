@@ -0,0 +1,208 @@
+//! A Monte Carlo path tracer, for global illumination.
+//!
+//! Each sample follows a single path of rays through the scene,
+//! picking a random direction at every surface it hits.
+//! Averaged over many samples, light bounced between objects shows up,
+//! along with soft shadows from area lights and light given off by emissive materials.
+//!
+//! https://www.pbr-book.org/3ed-2018/Light_Transport_I_Surface_Reflection/Path_Tracing
+
+use core::f32::consts::{PI, TAU};
+
+use crate::environment::Texel;
+use crate::light::Incoming;
+use crate::march::{self, Penumbra, Shadows, Trace};
+use crate::material;
+use crate::random::Rng;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::sdf::MaterialSdf;
+use crate::vector::prelude::*;
+
+/// How the light reaching the camera is worked out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Integrator {
+    /// Direct lighting with shadows and ambient occlusion,
+    /// following mirror reflections and refractions.
+    #[default]
+    Whitted,
+    /// A path tracer, see the [module docs](self).
+    Path,
+}
+
+/// How many bounces a path takes before Russian roulette can end it.
+pub const MIN_BOUNCES: u32 = 3;
+
+/// The light found along a path.
+pub struct Path<V: Comp<3>> {
+    pub color: V::Vec,
+    /// The normal of the first surface the path hit.
+    pub normal: V::Vec,
+    /// `1` in lanes where the first ray hit something, otherwise `0`.
+    pub hit: V,
+    /// The trace of the first ray.
+    pub trace: Trace<V, V::Mask>,
+}
+
+/// Follows a path from `ray` for each lane, bouncing up to `bounces` times.
+///
+/// Lights are sampled at every bounce, which is checked for shadows if `shadows` is set.
+pub fn trace<V, S>(
+    scene: &Scene<S>,
+    ray: Ray<V>,
+    rng: &mut Rng<V>,
+    bounces: u32,
+    shadows: bool,
+) -> Path<V>
+where
+    V: Comp<2> + Comp<3> + march::Traceable + Texel,
+    S: MaterialSdf<V>,
+{
+    let map = |p| scene.root.dist(p);
+    let zero = <V as Comp<3>>::Vec::ZERO;
+    // shadow rays stop at anything in the way, soft shadows come from lights with an area
+    let hard = Shadows {
+        penumbra: Penumbra::Hard,
+        ..Shadows::default()
+    };
+
+    let mut ray = ray;
+    // how much of the light from the rest of the path makes it back to the camera
+    let mut throughput = broadcast::<3, V>(V::ONE);
+    let mut color = zero;
+    // `1` for lanes outside of an object, `-1` inside
+    let mut side = V::ONE;
+    let mut first = None;
+
+    for depth in 0..=bounces {
+        // inside of an object the distance field is negative, so march it's negation
+        let inner = |p| map(p) * side;
+        // only rays from the camera start at the near plane
+        let settings = match depth {
+            0 => scene.march,
+            _ => scene.march.secondary(),
+        };
+        let trace = march::trace(&inner, ray, &settings);
+        let Trace { distance, hit, .. } = trace;
+        let outside = side.simd_gt(V::ZERO);
+        let pos = ray.at(distance);
+        // the surface normal, facing back towards the ray
+        let nor = march::normal(&inner, pos, distance, &settings);
+        first.get_or_insert((nor, hit.select(V::ONE, V::ZERO), trace));
+        // paths that miss everything see the environment, and end
+        let background = scene.environment.radiance::<V>(ray.dir);
+        color += throughput * select::<3, V>(hit, zero, background);
+        throughput = select::<3, V>(hit, throughput, zero);
+        if !hit.any() {
+            break;
+        }
+
+        let (_, id) = scene.root.dist_mat(pos);
+        let surface = material::surface(&scene.materials, id);
+        let eye = -ray.dir;
+        // the inside of objects isn't lit
+        let lit = hit & outside;
+
+        // EMISSION
+
+        color += throughput * select::<3, V>(lit, surface.emissive, zero);
+
+        // NEXT EVENT ESTIMATION
+
+        // light from a random point on every light
+        let area = rng.point();
+        for light in &scene.lights {
+            let Incoming {
+                dir,
+                dist,
+                radiance,
+            } = light.incoming::<V>(pos, area);
+            let visible = if shadows {
+                // start just above the surface so the shadow ray doesn't hit it
+                let origin = pos + nor * settings.offset(distance);
+                march::shadow(&map, Ray::new(origin, dir), dist, hard, &settings)
+            } else {
+                V::ONE
+            };
+            let reflected = material::brdf(&surface, dir, radiance * visible, nor, eye);
+            color += throughput * select::<3, V>(lit, reflected, zero);
+        }
+
+        if depth == bounces {
+            break;
+        }
+
+        // THE NEXT RAY
+
+        let (reflected, refracted) = material::scatter(&surface, ray.dir, nor, outside);
+        // pick between a diffuse bounce, a mirror reflection or a refraction,
+        // in proportion to how much light each carries
+        let diffuse = outside.select(material::direct(&surface), V::ZERO);
+        let mirror = reflected.color.max_element();
+        let glass = refracted.color.max_element();
+        let total = diffuse + mirror + glass;
+        let [pick, _] = rng.point().to_array();
+        let pick = pick * total;
+        let is_diffuse = pick.simd_lt(diffuse);
+        let is_mirror = !is_diffuse & pick.simd_lt(diffuse + mirror);
+        let is_glass = !is_diffuse & !is_mirror;
+
+        // cosine weighted direction around the normal
+        let [s, t] = rng.point().to_array();
+        let (side_axis, top_axis) = basis::<V>(nor);
+        let (r, phi) = (s.sqrt(), t * V::from(TAU));
+        let bounce = side_axis * (r * phi.cos())
+            + top_axis * (r * phi.sin())
+            + nor * (V::ONE - s).max(V::ZERO).sqrt();
+        // the BRDF times the cosine over the pdf of the direction, cos / pi
+        let cos = nor.dot(bounce).max(V::from(1e-6));
+        let white = broadcast::<3, V>(V::ONE);
+        let diffuse_weight =
+            material::brdf(&surface, bounce, white, nor, eye) * (V::from(PI) / cos);
+
+        // divide by the chance of picking each kind of ray
+        let weight = select::<3, V>(
+            is_diffuse,
+            diffuse_weight * (total / diffuse),
+            select::<3, V>(
+                is_mirror,
+                reflected.color * (total / mirror),
+                refracted.color * (total / glass),
+            ),
+        );
+        // surfaces that don't carry any light on end the path
+        throughput = select::<3, V>(total.simd_gt(V::ZERO) & hit, throughput * weight, zero);
+
+        // RUSSIAN ROULETTE
+
+        if depth >= MIN_BOUNCES {
+            // paths carrying less light are more likely to end,
+            // the ones that carry on are brighter to make up for it
+            let survive = throughput.max_element().min(V::from(0.95));
+            let [u, _] = rng.point().to_array();
+            throughput = select::<3, V>(u.simd_lt(survive), throughput / survive, zero);
+        }
+        if !throughput.max_element().simd_gt(V::ZERO).any() {
+            break;
+        }
+
+        let dir = select::<3, V>(
+            is_diffuse,
+            bounce,
+            select::<3, V>(is_mirror, reflected.dir, refracted.dir),
+        );
+        // refracted rays start just below the surface, the others just above
+        let offset = nor * settings.offset(distance);
+        let origin = select::<3, V>(is_glass, pos - offset, pos + offset);
+        side = is_glass.select(-side, side);
+        ray = Ray::new(origin, dir);
+    }
+
+    let (normal, hit, trace) = first.unwrap();
+    Path {
+        color,
+        normal,
+        hit,
+        trace,
+    }
+}
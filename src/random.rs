@@ -0,0 +1,95 @@
+//! Random numbers for sampling.
+//!
+//! Everything is hashed from it's inputs,
+//! so rendering the same image twice gives the same result.
+
+use crate::vector::prelude::*;
+use crate::vector::CompOps;
+
+/// The `i`th point of the R2 sequence, spread evenly over `[0, 1)` on both axes.
+///
+/// https://extremelearning.com.au/unreasonable-effectiveness-of-quasirandom-sequences/
+pub fn r2(i: u32) -> Vec2 {
+    // 1 / g and 1 / g^2, where g is the plastic number
+    const A1: f64 = 0.754_877_666_246_692_7;
+    const A2: f64 = 0.569_840_290_998_053_2;
+    let i = i as f64;
+    let fract = |x: f64| (x - x.floor()) as f32;
+    vec2(fract(0.5 + A1 * i), fract(0.5 + A2 * i))
+}
+
+/// The `i`th point of the R2 sequence,
+/// shifted by a random amount for each lane of `seed` so that neighbouring pixels don't match.
+#[inline]
+pub fn sample<V: Comp<2>>(i: u32, seed: V::Vec) -> V::Vec {
+    let [x, y] = r2(i).to_array();
+    let [dx, dy] = hash2::<V>(seed).to_array();
+    vec2(fract(V::from(x) + dx), fract(V::from(y) + dy))
+}
+
+/// A pseudo random point in `[0, 1)` on both axes for each lane of `p`.
+///
+/// https://www.shadertoy.com/view/4djSRW
+#[inline]
+pub fn hash2<V: Comp<2>>(p: V::Vec) -> V::Vec {
+    let [x, y] = p.to_array();
+    let (x, y, z) = (
+        fract(x * V::from(0.1031)),
+        fract(y * V::from(0.1030)),
+        fract(x * V::from(0.0973)),
+    );
+    let d = x * (y + V::from(33.33)) + y * (z + V::from(33.33)) + z * (x + V::from(33.33));
+    let (x, y, z) = (x + d, y + d, z + d);
+    vec2(fract((x + y) * z), fract((x + z) * y))
+}
+
+/// A pseudo random number in `[0, 1)` for each lane of the 3D point `p`.
+///
+/// https://www.shadertoy.com/view/4djSRW
+#[inline]
+pub fn hash3<V: Comp<3>>(p: V::Vec) -> V {
+    let [x, y, z] = p.to_array();
+    let (x, y, z) = (
+        fract(x * V::from(0.1031)),
+        fract(y * V::from(0.1031)),
+        fract(z * V::from(0.1031)),
+    );
+    let d = x * (z + V::from(31.32)) + y * (y + V::from(31.32)) + z * (x + V::from(31.32));
+    fract((x + y + d + d) * (z + d))
+}
+
+/// Random points for one sample of each lane, a new point every time one is taken.
+///
+/// The points only depend on the sample, the seed of each lane and how many points came before,
+/// so the same path is taken every time a pixel is rendered.
+pub struct Rng<V: Comp<2>> {
+    i: u32,
+    seed: V::Vec,
+    taken: u32,
+}
+
+impl<V: Comp<2>> Rng<V> {
+    /// Points for the `i`th sample of each lane of `seed`.
+    pub fn new(i: u32, seed: V::Vec) -> Self {
+        Self { i, seed, taken: 0 }
+    }
+
+    /// The next random point, in `[0, 1)` on both axes.
+    #[inline]
+    pub fn point(&mut self) -> V::Vec {
+        self.taken += 1;
+        // spread the sample and the number of points taken over both axes,
+        // so every pair of them hashes to a different point
+        let shift = vec2(
+            V::from(self.i as f32 * 1.618_034 + self.taken as f32 * 12.9898),
+            V::from(self.i as f32 * 2.236_068 + self.taken as f32 * 78.233),
+        );
+        hash2::<V>(self.seed + shift)
+    }
+}
+
+/// The fractional part of `x`.
+#[inline]
+fn fract<V: CompOps + Copy>(x: V) -> V {
+    x - x.floor()
+}
@@ -0,0 +1,235 @@
+//! Where the samples inside of each pixel are taken, and how they're combined.
+
+use crate::random;
+use crate::vector::prelude::*;
+
+/// How many samples are taken for each pixel, and where.
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler {
+    pub samples: u32,
+    pub pattern: Pattern,
+    pub filter: Filter,
+    /// Which pass of a progressive render the samples are for,
+    /// each pass takes the samples in the same places with different random numbers.
+    pub pass: u32,
+}
+
+impl Sampler {
+    /// The number of the `i`th sample out of the samples of every pass so far, to seed random numbers with.
+    #[inline]
+    pub fn seed(&self, i: u32) -> u32 {
+        self.pass * self.samples + i
+    }
+
+    /// Where the `i`th sample is taken, relative to the middle of the pixel at `pos`.
+    ///
+    /// Samples are spread over the area covered by the filter.
+    #[inline]
+    pub fn offset<V: Comp<2>>(&self, i: u32, pos: V::Vec) -> V::Vec {
+        let [x, y] = self.pattern.point::<V>(i, self.samples, pos).to_array();
+        let r = V::from(self.filter.radius());
+        let two = V::from(2.0);
+        vec2((x * two - V::ONE) * r, (y * two - V::ONE) * r)
+    }
+
+    /// How much a sample at `offset` from the middle of the pixel counts towards it's color.
+    #[inline]
+    pub fn weight<V: Comp<2>>(&self, offset: V::Vec) -> V {
+        let [x, y] = offset.to_array();
+        self.filter.weight(x) * self.filter.weight(y)
+    }
+}
+
+/// The pattern of points that samples are taken at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pattern {
+    /// Evenly spaced rows and columns.
+    #[default]
+    Grid,
+    /// A grid tilted so that no two samples share a row or column.
+    RotatedGrid,
+    /// The Halton sequence in bases 2 and 3.
+    Halton,
+    /// The R2 sequence, see [`random::r2`].
+    R2,
+    /// A random point inside of each cell of a grid.
+    Jitter,
+}
+
+impl Pattern {
+    /// The `i`th of `n` points, from `0` to `1` on both axes.
+    ///
+    /// Only [`Pattern::Jitter`] is different for each pixel at `pos`.
+    #[inline]
+    pub fn point<V: Comp<2>>(self, i: u32, n: u32, pos: V::Vec) -> V::Vec {
+        // the columns of the smallest square grid that fits all of the points,
+        // with as many rows as are needed, and the points of the last row spread over all of it
+        let columns = (n as f32).sqrt().ceil().max(1.0) as u32;
+        let rows = (n + columns - 1) / columns;
+        let row = i / columns;
+        let size = vec2(
+            columns.min(n.saturating_sub(row * columns)).max(1) as f32,
+            rows.max(1) as f32,
+        );
+        let (x, y) = ((i % columns) as f32, row as f32);
+        let scalar = |p: Vec2| vec2(V::from(p.x), V::from(p.y));
+
+        match self {
+            Pattern::Grid => scalar(vec2((x + 0.5) / size.x, (y + 0.5) / size.y)),
+            Pattern::RotatedGrid => {
+                // tilt by atan(1/2), the points wrap around so they still cover the pixel evenly
+                let (sin, cos) = (0.5_f32).atan().sin_cos();
+                let p = vec2((x + 0.5) / size.x - 0.5, (y + 0.5) / size.y - 0.5);
+                let p = vec2(p.x * cos - p.y * sin + 0.5, p.x * sin + p.y * cos + 0.5);
+                scalar(vec2(p.x - p.x.floor(), p.y - p.y.floor()))
+            }
+            Pattern::Halton => scalar(vec2(halton(i + 1, 2), halton(i + 1, 3))),
+            Pattern::R2 => scalar(random::r2(i)),
+            Pattern::Jitter => {
+                // a different random offset for each sample of each pixel
+                let seed = pos + broadcast::<2, V>(V::from(i as f32 * 7.31));
+                let [jx, jy] = random::hash2::<V>(seed).to_array();
+                vec2(
+                    (V::from(x) + jx) / V::from(size.x),
+                    (V::from(y) + jy) / V::from(size.y),
+                )
+            }
+        }
+    }
+}
+
+/// The `i`th number in the van der Corput sequence with `base`.
+fn halton(mut i: u32, base: u32) -> f32 {
+    let mut f = 1.0;
+    let mut r = 0.0;
+    while i > 0 {
+        f /= base as f32;
+        r += f * (i % base) as f32;
+        i /= base;
+    }
+    r
+}
+
+/// Weights samples by their distance from the middle of the pixel,
+/// to rebuild the image from the samples.
+///
+/// https://www.pbr-book.org/3ed-2018/Sampling_and_Reconstruction/Image_Reconstruction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    /// All samples inside of the pixel count the same.
+    #[default]
+    Box,
+    /// Falls off in a straight line, over a radius of one pixel.
+    Tent,
+    /// A Gaussian bell curve.
+    Gaussian,
+    /// The Mitchell-Netravali filter with `B = C = 1/3`, sharper than a Gaussian.
+    Mitchell,
+}
+
+impl Filter {
+    /// How far away from the middle of the pixel samples are taken, in pixels.
+    pub fn radius(self) -> f32 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    /// The weight of a sample at a distance of `x` pixels along one axis.
+    #[inline]
+    pub fn weight<V: Comp<2>>(self, x: V) -> V {
+        let x = x.abs();
+        match self {
+            Filter::Box => V::ONE,
+            Filter::Tent => (V::ONE - x).max(V::ZERO),
+            Filter::Gaussian => {
+                // standard deviation of half a pixel,
+                // shifted down so the weight reaches zero at the radius
+                let alpha = V::from(2.0);
+                let r = self.radius();
+                let edge = V::from((-2.0 * r * r).exp());
+                ((-alpha * x * x).exp() - edge).max(V::ZERO)
+            }
+            Filter::Mitchell => {
+                const B: f32 = 1.0 / 3.0;
+                const C: f32 = 1.0 / 3.0;
+                let (x2, x3) = (x * x, x * x * x);
+                let near = V::from(12.0 - 9.0 * B - 6.0 * C) * x3
+                    + V::from(-18.0 + 12.0 * B + 6.0 * C) * x2
+                    + V::from(6.0 - 2.0 * B);
+                let far = V::from(-B - 6.0 * C) * x3
+                    + V::from(6.0 * B + 30.0 * C) * x2
+                    + V::from(-12.0 * B - 48.0 * C) * x
+                    + V::from(8.0 * B + 24.0 * C);
+                let w = x.simd_lt(V::ONE).select(near, far);
+                let w = x.simd_lt(V::from(2.0)).select(w, V::ZERO);
+                w * V::from(1.0 / 6.0)
+            }
+        }
+    }
+}
+
+/// Only takes extra samples for pixels on the edges of objects.
+///
+/// A first pass takes one sample for each pixel,
+/// then pixels that are different enough from their neighbours are taken again
+/// with all of the samples from the [`Sampler`].
+#[derive(Clone, Copy, Debug)]
+pub struct Adaptive {
+    /// How different the brightness of neighbouring pixels can be, from `0` to `1`.
+    pub contrast: f32,
+    /// The smallest cosine of the angle between the normals of neighbouring pixels.
+    pub normal: f32,
+}
+
+impl Default for Adaptive {
+    fn default() -> Self {
+        Self {
+            contrast: 0.1,
+            // about 25 degrees
+            normal: 0.9,
+        }
+    }
+}
+
+/// What the first pass of [`Adaptive`] sampling found at a pixel.
+#[derive(Clone, Copy, Debug)]
+pub struct Pixel {
+    /// The final color of the pixel, after post processing.
+    pub color: Vec3,
+    pub normal: Vec3,
+    pub hit: bool,
+}
+
+impl Adaptive {
+    /// The indices of the pixels that are different from any of the pixels next to them.
+    ///
+    /// `pixel` is what the first pass found at each index.
+    pub fn edges(&self, width: u32, height: u32, pixel: impl Fn(u32) -> Pixel + Sync) -> Vec<u32> {
+        use rayon::prelude::*;
+
+        // perceived brightness
+        let luma = |c: Vec3| c.dot(vec3(0.2126, 0.7152, 0.0722));
+        let differs = |a: Pixel, b: Pixel| {
+            a.hit != b.hit
+                || (luma(a.color) - luma(b.color)).abs() > self.contrast
+                // the background doesn't have a normal
+                || (a.hit && a.normal.dot(b.normal) < self.normal)
+        };
+
+        (0..width * height)
+            .into_par_iter()
+            .filter(|&i| {
+                let (x, y) = (i % width, i / width);
+                let p = pixel(i);
+                (x > 0 && differs(p, pixel(i - 1)))
+                    || (x + 1 < width && differs(p, pixel(i + 1)))
+                    || (y > 0 && differs(p, pixel(i - width)))
+                    || (y + 1 < height && differs(p, pixel(i + width)))
+            })
+            .collect()
+    }
+}
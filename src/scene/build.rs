@@ -0,0 +1,845 @@
+//! Builds a [`Scene`] from the parsed items.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::parse::{Attr, Item, Value};
+use super::{Error, Scene, CAMERA, LIGHT, UP};
+use crate::camera::{Equirectangular, Fisheye, Orthographic, Perspective, Projection, ThinLens};
+use crate::environment::{self, Environment, Sky};
+use crate::fog::{Fog, Media};
+use crate::light::Light;
+use crate::march::{Epsilon, MarchSettings, EPSILON};
+use crate::material::{Material, Model};
+use crate::sdf::{self, Blend, Node, Op, Shape};
+use crate::vector::prelude::*;
+
+/// Builds the scene from the top level `items`, with files relative to `dir`.
+pub fn build(items: Vec<Item>, dir: &Path) -> Result<Scene<Node>, Error> {
+    let mut builder = Builder::default();
+    let mut camera = None;
+    let mut lights = Vec::new();
+    let mut environment = None;
+    let mut fog = None;
+    let mut march = None;
+    let mut objects = Vec::new();
+
+    for item in &items {
+        match item.name.as_str() {
+            "camera" => {
+                if camera.is_some() {
+                    return Err(item.error("the scene can only have one `camera`"));
+                }
+                camera = Some(self::camera(item)?);
+            }
+            "light" => lights.push(light(item)?),
+            // built once all of the lights are known, as the sky follows the sun
+            "environment" => {
+                if environment.is_some() {
+                    return Err(item.error("the scene can only have one `environment`"));
+                }
+                environment = Some(item);
+            }
+            "fog" => {
+                if fog.is_some() {
+                    return Err(item.error("the scene can only have one `fog`"));
+                }
+                fog = Some(self::fog(item)?);
+            }
+            "march" => {
+                if march.is_some() {
+                    return Err(item.error("the scene can only have one `march`"));
+                }
+                march = Some(self::march(item)?);
+            }
+            "material" => {
+                let (name, material) = self::material(item)?;
+                if builder.names.contains_key(&name) {
+                    return Err(item.error(format!("material `{name}` is already defined")));
+                }
+                builder.names.insert(name, builder.materials.len() as u32);
+                builder.materials.push(material);
+            }
+            _ => objects.push(builder.node(item)?),
+        }
+    }
+
+    if objects.is_empty() {
+        return Err(Error::new(1, 1, "the scene doesn't have any objects"));
+    }
+    if lights.is_empty() {
+        lights.push(Light::directional(LIGHT));
+    }
+    let environment = match environment {
+        Some(item) => self::environment(item, &lights, dir)?,
+        None => Environment::default(),
+    };
+
+    Ok(Scene {
+        camera: camera.unwrap_or_else(|| {
+            Projection::Perspective(Perspective::look_at(CAMERA, Vec3::ZERO, UP))
+        }),
+        lights,
+        environment,
+        fog,
+        march: march.unwrap_or_default(),
+        materials: builder.materials,
+        root: union(objects),
+    })
+}
+
+/// Builds the camera, which is a perspective camera by default.
+fn camera(item: &Item) -> Result<Projection, Error> {
+    let mut attrs = Attrs::new(item)?;
+    let eye = attrs.vec3("position")?.unwrap_or(CAMERA);
+    // looks down the negative Z axis by default
+    let target = attrs
+        .vec3("target")?
+        .unwrap_or(eye - Vec3::new(0.0, 0.0, 1.0));
+    let up = attrs.vec3("up")?.unwrap_or(UP);
+    let roll = attrs.number("roll")?.unwrap_or(0.0);
+    if (target - eye).length_sq() == 0.0 {
+        return Err(attrs.error("target", "the camera can't look at itself"));
+    }
+    if up.length_sq() == 0.0 {
+        return Err(attrs.error("up", "`up` can't be zero"));
+    }
+    // the image can't be turned to face up when the camera is looking straight along `up`
+    let forward = target - eye;
+    if cross::<f32>(forward, up).length_sq() <= 1e-6 * forward.length_sq() * up.length_sq() {
+        return Err(attrs.error(
+            "up",
+            "`up` can't point the same way as the camera is looking",
+        ));
+    }
+
+    let projection = match attrs.ident("projection")?.as_deref() {
+        None | Some("perspective") => {
+            // a perspective camera can't see 180 degrees or more
+            let fov = fov(&mut attrs, Perspective::FOV, 179.0)?;
+            Projection::Perspective(
+                Perspective::look_at(eye, target, up)
+                    .with_fov(fov)
+                    .with_roll(roll),
+            )
+        }
+        Some("orthographic") => {
+            let height = match attrs.number("height")? {
+                Some(height) if height <= 0.0 => {
+                    return Err(attrs.error("height", "`height` must be greater than 0"));
+                }
+                height => height.unwrap_or(Orthographic::HEIGHT),
+            };
+            Projection::Orthographic(
+                Orthographic::look_at(eye, target, up)
+                    .with_height(height)
+                    .with_roll(roll),
+            )
+        }
+        Some("fisheye") => {
+            let fov = fov(&mut attrs, Fisheye::FOV, 360.0)?;
+            Projection::Fisheye(
+                Fisheye::look_at(eye, target, up)
+                    .with_fov(fov)
+                    .with_roll(roll),
+            )
+        }
+        Some("thin_lens") => {
+            let fov = fov(&mut attrs, Perspective::FOV, 179.0)?;
+            let aperture = match attrs.number("aperture")? {
+                Some(aperture) if aperture < 0.0 => {
+                    return Err(attrs.error("aperture", "`aperture` can't be negative"));
+                }
+                aperture => aperture.unwrap_or(ThinLens::APERTURE),
+            };
+            let camera = ThinLens::look_at(eye, target, up)
+                .with_fov(fov)
+                .with_aperture(aperture)
+                .with_roll(roll);
+            // focuses on the target by default
+            let camera = match positive(&mut attrs, "focus")? {
+                Some(focus) => camera.with_focus(focus),
+                None => camera,
+            };
+            Projection::ThinLens(camera)
+        }
+        Some("equirectangular") => Projection::Equirectangular(
+            Equirectangular::look_at(eye, target, up).with_roll(roll),
+        ),
+        Some(other) => {
+            return Err(attrs.error(
+                "projection",
+                format!(
+                    "unknown projection `{other}`, expected `perspective`, `thin_lens`, `orthographic`, `fisheye` or `equirectangular`"
+                ),
+            ))
+        }
+    };
+
+    attrs.finish()?;
+    item.no_children()?;
+    Ok(projection)
+}
+
+/// Builds a light, which is directional by default.
+fn light(item: &Item) -> Result<Light, Error> {
+    let mut attrs = Attrs::new(item)?;
+    let light = match attrs.ident("type")?.as_deref() {
+        None | Some("directional") => {
+            let dir = attrs.required(Attrs::vec3, "direction")?;
+            if dir.length_sq() == 0.0 {
+                return Err(attrs.error("direction", "`direction` can't be zero"));
+            }
+            Light::directional(dir)
+        }
+        Some("point") => Light::point(attrs.required(Attrs::vec3, "position")?),
+        Some("spot") => {
+            let position = attrs.required(Attrs::vec3, "position")?;
+            let target = attrs.required(Attrs::vec3, "target")?;
+            if (target - position).length_sq() == 0.0 {
+                return Err(attrs.error("target", "the light can't point at itself"));
+            }
+            let inner = attrs.number("inner")?.unwrap_or(Light::INNER);
+            let outer = attrs.number("outer")?.unwrap_or(Light::OUTER);
+            if !(0.0..180.0).contains(&outer) {
+                return Err(attrs.error("outer", "`outer` must be between 0 and 180 degrees"));
+            }
+            if !(0.0..outer).contains(&inner) {
+                return Err(attrs.error(
+                    "inner",
+                    "`inner` must be between 0 degrees and `outer`",
+                ));
+            }
+            Light::spot(position, target).with_cone(inner, outer)
+        }
+        Some("sphere") => {
+            let position = attrs.required(Attrs::vec3, "position")?;
+            let radius = size(&mut attrs, "radius")?;
+            Light::sphere(position, radius)
+        }
+        Some("rect") => {
+            let position = attrs.required(Attrs::vec3, "position")?;
+            let target = attrs.required(Attrs::vec3, "target")?;
+            let width = size(&mut attrs, "width")?;
+            let height = size(&mut attrs, "height")?;
+            if (target - position).length_sq() == 0.0 {
+                return Err(attrs.error("target", "the light can't face itself"));
+            }
+            Light::rect(position, target, width, height)
+        }
+        Some(other) => {
+            return Err(attrs.error(
+                "type",
+                format!(
+                    "unknown light `{other}`, expected `directional`, `point`, `spot`, `sphere` or `rect`"
+                ),
+            ))
+        }
+    };
+    let color = attrs.vec3("color")?.unwrap_or(Vec3::ONE);
+    let intensity = attrs.number("intensity")?.unwrap_or(1.0);
+
+    attrs.finish()?;
+    item.no_children()?;
+    Ok(light.with_color(color).with_intensity(intensity))
+}
+
+/// Builds the environment, the sky follows the first directional light in `lights` by default.
+fn environment(item: &Item, lights: &[Light], dir: &Path) -> Result<Environment, Error> {
+    let mut attrs = Attrs::new(item)?;
+    let environment = match attrs.required(Attrs::ident, "type")?.as_str() {
+        "solid" => Environment::solid(attrs.required(Attrs::vec3, "color")?),
+        "gradient" => {
+            let top = attrs.vec3("top")?.unwrap_or(Environment::TOP);
+            let bottom = attrs.vec3("bottom")?.unwrap_or(Environment::BOTTOM);
+            Environment::gradient(top, bottom)
+        }
+        "sky" => {
+            let sun = match attrs.vec3("sun")? {
+                Some(sun) if sun.length_sq() == 0.0 => {
+                    return Err(attrs.error("sun", "`sun` can't be zero"));
+                }
+                sun => sun.unwrap_or_else(|| super::sun(lights)),
+            };
+            let turbidity = match attrs.number("turbidity")? {
+                Some(t) if !(1.7..=10.0).contains(&t) => {
+                    return Err(attrs.error("turbidity", "`turbidity` must be between 1.7 and 10"));
+                }
+                t => t.unwrap_or(Sky::TURBIDITY),
+            };
+            Environment::sky(sun, turbidity)
+        }
+        "image" => {
+            let path = dir.join(attrs.required(Attrs::string, "path")?);
+            let image = environment::Image::load(&path).map_err(|e| {
+                attrs.error("path", format!("couldn't load `{}`: {e}", path.display()))
+            })?;
+            Environment::image(image)
+        }
+        other => {
+            return Err(attrs.error(
+                "type",
+                format!(
+                    "unknown environment `{other}`, expected `solid`, `gradient`, `sky` or `image`"
+                ),
+            ))
+        }
+    };
+    let intensity = attrs.number("intensity")?.unwrap_or(1.0);
+
+    attrs.finish()?;
+    item.no_children()?;
+    Ok(environment.with_intensity(intensity))
+}
+
+/// Builds the fog, which gets thicker with distance by default.
+fn fog(item: &Item) -> Result<Fog, Error> {
+    let mut attrs = Attrs::new(item)?;
+    let density = match attrs.number("density")? {
+        Some(density) if density <= 0.0 => {
+            return Err(attrs.error("density", "`density` must be greater than 0"));
+        }
+        density => density.unwrap_or(Fog::DENSITY),
+    };
+    let fog = match attrs.ident("type")?.as_deref() {
+        None | Some("distance") => Fog::distance(density),
+        Some("height") => {
+            let falloff = match attrs.number("falloff")? {
+                Some(falloff) if falloff <= 0.0 => {
+                    return Err(attrs.error("falloff", "`falloff` must be greater than 0"));
+                }
+                falloff => falloff.unwrap_or(Fog::FALLOFF),
+            };
+            let height = attrs.number("height")?.unwrap_or(0.0);
+            Fog::height(density, falloff, height)
+        }
+        Some("media") => {
+            let albedo = attrs.vec3("albedo")?.unwrap_or(Vec3::ONE);
+            let anisotropy = match attrs.number("anisotropy")? {
+                Some(g) if !(-0.99..=0.99).contains(&g) => {
+                    return Err(
+                        attrs.error("anisotropy", "`anisotropy` must be between -0.99 and 0.99")
+                    );
+                }
+                g => g.unwrap_or(0.0),
+            };
+            let steps = match attrs.number("steps")? {
+                Some(steps) if steps < 1.0 || steps.fract() != 0.0 => {
+                    return Err(attrs.error("steps", "`steps` must be a whole number above 0"));
+                }
+                steps => steps.map_or(Media::STEPS, |steps| steps as u32),
+            };
+            let media = Media::new(density)
+                .with_albedo(albedo)
+                .with_anisotropy(anisotropy)
+                .with_steps(steps);
+            // patchy if the clumps are given a size
+            let media = match attrs.number("noise")? {
+                Some(scale) if scale <= 0.0 => {
+                    return Err(attrs.error("noise", "`noise` must be greater than 0"));
+                }
+                Some(scale) => media.with_noise(scale),
+                None => media,
+            };
+            Fog::Media(media)
+        }
+        Some(other) => {
+            return Err(attrs.error(
+                "type",
+                format!("unknown fog `{other}`, expected `distance`, `height` or `media`"),
+            ))
+        }
+    };
+    // the color of media comes from the lights
+    let fog = match (fog, attrs.vec3("color")?) {
+        (Fog::Media(_), Some(_)) => {
+            return Err(attrs.error(
+                "color",
+                "`color` can't be used with `type=media`, use `albedo` instead",
+            ));
+        }
+        (fog, Some(color)) => fog.with_color(color),
+        (fog, None) => fog,
+    };
+
+    attrs.finish()?;
+    item.no_children()?;
+    Ok(fog)
+}
+
+/// Builds the settings for marching rays, with a relative epsilon by default.
+fn march(item: &Item) -> Result<MarchSettings, Error> {
+    let mut attrs = Attrs::new(item)?;
+    let default = MarchSettings::default();
+    let epsilon = match attrs.number("epsilon")? {
+        Some(epsilon) if epsilon <= 0.0 => {
+            return Err(attrs.error("epsilon", "`epsilon` must be greater than 0"));
+        }
+        epsilon => epsilon.unwrap_or(EPSILON),
+    };
+    let epsilon = match attrs.ident("mode")?.as_deref() {
+        None | Some("relative") => Epsilon::Relative(epsilon),
+        Some("absolute") => Epsilon::Absolute(epsilon),
+        Some(other) => {
+            return Err(attrs.error(
+                "mode",
+                format!("unknown mode `{other}`, expected `relative` or `absolute`"),
+            ))
+        }
+    };
+    let max_steps = match attrs.number("steps")? {
+        Some(steps) if steps < 1.0 || steps.fract() != 0.0 => {
+            return Err(attrs.error("steps", "`steps` must be a whole number above 0"));
+        }
+        steps => steps.map_or(default.max_steps, |steps| steps as u32),
+    };
+    let near = match attrs.number("near")? {
+        Some(near) if near < 0.0 => {
+            return Err(attrs.error("near", "`near` can't be negative"));
+        }
+        near => near.unwrap_or(default.near),
+    };
+    let far = attrs.number("far")?.unwrap_or(default.far);
+    if far <= near {
+        return Err(attrs.error("far", "`far` must be greater than `near`"));
+    }
+    let relaxation = unit(&mut attrs, "relaxation")?.or(default.relaxation);
+
+    attrs.finish()?;
+    item.no_children()?;
+    Ok(MarchSettings {
+        epsilon,
+        max_steps,
+        near,
+        far,
+        relaxation,
+    })
+}
+
+/// Builds a named material, which uses the Phong model by default.
+fn material(item: &Item) -> Result<(String, Material), Error> {
+    let mut attrs = Attrs::new(item)?;
+    let name = attrs.required(Attrs::ident, "name")?;
+    let color = attrs.required(Attrs::vec3, "color")?;
+    let emissive = attrs.vec3("emissive")?.unwrap_or(Vec3::ZERO);
+    let reflectivity = unit(&mut attrs, "reflectivity")?.unwrap_or(0.0);
+    let transmission = unit(&mut attrs, "transmission")?.unwrap_or(0.0);
+    let ior = match attrs.number("ior")? {
+        Some(ior) if ior < 1.0 => {
+            return Err(attrs.error("ior", "`ior` can't be less than 1"));
+        }
+        ior => ior.unwrap_or(Material::IOR),
+    };
+    if reflectivity + transmission > 1.0 {
+        return Err(attrs.error(
+            "transmission",
+            "`reflectivity` and `transmission` can't add up to more than 1",
+        ));
+    }
+    let material = Material::new(color)
+        .with_emissive(emissive)
+        .with_reflectivity(reflectivity)
+        .with_transmission(transmission, ior);
+
+    let material = match attrs.ident("model")?.as_deref() {
+        None | Some("phong") => {
+            for attr in ["metallic", "roughness"] {
+                if attrs.number(attr)?.is_some() {
+                    return Err(attrs.error(
+                        attr,
+                        format!("`{attr}` can only be used with `model=cook_torrance`"),
+                    ));
+                }
+            }
+            material
+        }
+        Some("cook_torrance") => {
+            let metallic = unit(&mut attrs, "metallic")?.unwrap_or(0.0);
+            let roughness = unit(&mut attrs, "roughness")?.unwrap_or(Material::ROUGHNESS);
+            material
+                .with_model(Model::CookTorrance)
+                .with_metallic(metallic)
+                .with_roughness(roughness)
+        }
+        Some(other) => {
+            return Err(attrs.error(
+                "model",
+                format!("unknown model `{other}`, expected `phong` or `cook_torrance`"),
+            ))
+        }
+    };
+
+    attrs.finish()?;
+    item.no_children()?;
+    Ok((name, material))
+}
+
+/// Gets a size that the item must have, which must be greater than 0.
+fn size(attrs: &mut Attrs, name: &str) -> Result<f32, Error> {
+    attrs.required(positive, name)
+}
+
+/// A number attribute that must be greater than 0.
+fn positive(attrs: &mut Attrs, name: &str) -> Result<Option<f32>, Error> {
+    match attrs.number(name)? {
+        Some(n) if n <= 0.0 => Err(attrs.error(name, format!("`{name}` must be greater than 0"))),
+        n => Ok(n),
+    }
+}
+
+/// Gets sizes in each direction that the item must have, which must all be greater than 0.
+fn sizes(attrs: &mut Attrs, name: &str) -> Result<Vec3, Error> {
+    let v = attrs.required(Attrs::vec3, name)?;
+    if v.min_element() <= 0.0 {
+        return Err(attrs.error(
+            name,
+            format!("every part of `{name}` must be greater than 0"),
+        ));
+    }
+    Ok(v)
+}
+
+/// A vector attribute that's used as a direction, which can't be zero.
+fn direction(attrs: &mut Attrs, name: &str) -> Result<Option<Vec3>, Error> {
+    match attrs.vec3(name)? {
+        Some(v) if v.length_sq() == 0.0 => {
+            Err(attrs.error(name, format!("`{name}` can't be zero")))
+        }
+        v => Ok(v),
+    }
+}
+
+/// A number attribute that must be inside of `[0, 1]`.
+fn unit(attrs: &mut Attrs, name: &str) -> Result<Option<f32>, Error> {
+    match attrs.number(name)? {
+        Some(n) if !(0.0..=1.0).contains(&n) => {
+            Err(attrs.error(name, format!("`{name}` must be between 0 and 1")))
+        }
+        n => Ok(n),
+    }
+}
+
+/// The field of view of a camera, which must be inside of `(0, max]` degrees.
+fn fov(attrs: &mut Attrs, default: f32, max: f32) -> Result<f32, Error> {
+    match attrs.number("fov")? {
+        Some(fov) if fov <= 0.0 || fov > max => {
+            Err(attrs.error("fov", format!("`fov` must be between 0 and {max} degrees")))
+        }
+        fov => Ok(fov.unwrap_or(default)),
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    materials: Vec<Material>,
+    /// The id of each material by name.
+    names: HashMap<String, u32>,
+}
+
+impl Builder {
+    /// Builds a shape, combinator or transform.
+    fn node(&self, item: &Item) -> Result<Node, Error> {
+        let mut attrs = Attrs::new(item)?;
+        let node = match item.name.as_str() {
+            "union" | "intersect" | "subtract" => {
+                let op = match item.name.as_str() {
+                    "union" => Op::Union,
+                    "intersect" => Op::Intersection,
+                    _ => Op::Subtraction,
+                };
+                let blend = match attrs.ident("blend")?.as_deref() {
+                    None | Some("polynomial") => Blend::Polynomial,
+                    Some("exponential") => Blend::Exponential,
+                    Some(other) => {
+                        return Err(attrs.error(
+                            "blend",
+                            format!(
+                                "unknown blend `{other}`, expected `polynomial` or `exponential`"
+                            ),
+                        ))
+                    }
+                };
+                let smooth = positive(&mut attrs, "smooth")?.map(|k| (k, blend));
+                let children = self.children(item)?;
+                if children.is_empty() {
+                    return Err(item.error(format!("`{}` needs at least one child", item.name)));
+                }
+                Node::Combine {
+                    op,
+                    children,
+                    smooth,
+                }
+            }
+            "translate" => {
+                let offset = attrs.required(Attrs::vec3, "offset")?;
+                self.group(item)?.translated(offset)
+            }
+            "rotate" => {
+                let axis = attrs.required(direction, "axis")?;
+                let angle = attrs.required(Attrs::number, "angle")?;
+                Node::rotate(self.group(item)?, axis, angle.to_radians())
+            }
+            "scale" => {
+                let factor = size(&mut attrs, "factor")?;
+                self.group(item)?.scaled(factor)
+            }
+            "transform" => {
+                let translation = attrs.vec3("translation")?.unwrap_or(Vec3::ZERO);
+                let axis = direction(&mut attrs, "axis")?.unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                let angle = attrs.number("angle")?.unwrap_or(0.0);
+                let scale = positive(&mut attrs, "scale")?.unwrap_or(1.0);
+                let mat = Mat4::from_scale_rotation_translation(
+                    Vec3::splat(scale),
+                    Quat::from_axis_angle(axis, angle.to_radians()),
+                    translation,
+                );
+                Node::transform(self.group(item)?, mat)
+            }
+            _ => {
+                let shape = shape(item, &mut attrs)?;
+                item.no_children()?;
+                Node::Shape(shape)
+            }
+        };
+
+        // any object can be given a material
+        let node = match attrs.ident("material")? {
+            Some(name) => match self.names.get(&name) {
+                Some(&id) => node.with_material(id),
+                None => {
+                    return Err(attrs.error("material", format!("unknown material `{name}`")));
+                }
+            },
+            None => node,
+        };
+        // and a name
+        let node = match attrs.ident("name")? {
+            Some(name) => node.named(name),
+            None => node,
+        };
+
+        attrs.finish()?;
+        Ok(node)
+    }
+
+    fn children(&self, item: &Item) -> Result<Vec<Node>, Error> {
+        item.children.iter().map(|c| self.node(c)).collect()
+    }
+
+    /// The children of a transform, which are combined if there is more than one.
+    fn group(&self, item: &Item) -> Result<Node, Error> {
+        let children = self.children(item)?;
+        if children.is_empty() {
+            return Err(item.error(format!("`{}` needs at least one child", item.name)));
+        }
+        Ok(union(children))
+    }
+}
+
+/// Builds a primitive shape.
+fn shape(item: &Item, attrs: &mut Attrs) -> Result<Shape, Error> {
+    let shape = match item.name.as_str() {
+        "sphere" => Shape::Sphere(sdf::Sphere(size(attrs, "radius")?)),
+        "box" => Shape::Box(sdf::Box(sizes(attrs, "size")?)),
+        "plane" => Shape::Plane(sdf::Plane {
+            normal: direction(attrs, "normal")?
+                .unwrap_or(Vec3::new(0.0, 1.0, 0.0))
+                .normalise(),
+            height: attrs.number("height")?.unwrap_or(0.0),
+        }),
+        "torus" => Shape::Torus(sdf::Torus {
+            major: size(attrs, "major")?,
+            minor: size(attrs, "minor")?,
+        }),
+        "cylinder" => Shape::CappedCylinder(sdf::CappedCylinder {
+            radius: size(attrs, "radius")?,
+            height: size(attrs, "height")?,
+        }),
+        "capsule" => {
+            let a = attrs.required(Attrs::vec3, "a")?;
+            let b = attrs.required(Attrs::vec3, "b")?;
+            if a == b {
+                return Err(attrs.error("b", "`a` and `b` can't be the same point"));
+            }
+            Shape::Capsule(sdf::Capsule {
+                a,
+                b,
+                radius: size(attrs, "radius")?,
+            })
+        }
+        "cone" => {
+            let angle = match attrs.required(Attrs::number, "angle")? {
+                angle if angle <= 0.0 || angle >= 90.0 => {
+                    return Err(attrs.error("angle", "`angle` must be between 0 and 90 degrees"));
+                }
+                angle => angle,
+            };
+            Shape::Cone(sdf::Cone::new(angle.to_radians(), size(attrs, "height")?))
+        }
+        "ellipsoid" => Shape::Ellipsoid(sdf::Ellipsoid(sizes(attrs, "radii")?)),
+        "round_box" => Shape::RoundBox(sdf::RoundBox {
+            size: sizes(attrs, "size")?,
+            radius: size(attrs, "radius")?,
+        }),
+        "octahedron" => Shape::Octahedron(sdf::Octahedron(size(attrs, "size")?)),
+        "hex_prism" => Shape::HexPrism(sdf::HexPrism {
+            radius: size(attrs, "radius")?,
+            height: size(attrs, "height")?,
+        }),
+        "triangle" => Shape::Triangle(sdf::Triangle {
+            a: attrs.required(Attrs::vec3, "a")?,
+            b: attrs.required(Attrs::vec3, "b")?,
+            c: attrs.required(Attrs::vec3, "c")?,
+        }),
+        "quad" => Shape::Quad(sdf::Quad {
+            a: attrs.required(Attrs::vec3, "a")?,
+            b: attrs.required(Attrs::vec3, "b")?,
+            c: attrs.required(Attrs::vec3, "c")?,
+            d: attrs.required(Attrs::vec3, "d")?,
+        }),
+        other => return Err(item.error(format!("unknown item `{other}`"))),
+    };
+    Ok(shape)
+}
+
+/// Combines the nodes, without wrapping a single node.
+fn union(mut nodes: Vec<Node>) -> Node {
+    if nodes.len() == 1 {
+        nodes.remove(0)
+    } else {
+        Node::union(nodes)
+    }
+}
+
+impl Item {
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::new(self.line, self.col, message)
+    }
+
+    fn no_children(&self) -> Result<(), Error> {
+        match self.children.first() {
+            Some(child) => Err(child.error(format!("`{}` can't have children", self.name))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The attributes of an item, keeping track of which have been used.
+struct Attrs<'a> {
+    item: &'a Item,
+    used: Vec<bool>,
+}
+
+impl<'a> Attrs<'a> {
+    fn new(item: &'a Item) -> Result<Self, Error> {
+        for (i, attr) in item.attrs.iter().enumerate() {
+            if item.attrs[..i].iter().any(|a| a.name == attr.name) {
+                return Err(Error::new(
+                    attr.line,
+                    attr.col,
+                    format!("`{}` is given more than once", attr.name),
+                ));
+            }
+        }
+        Ok(Self {
+            item,
+            used: vec![false; item.attrs.len()],
+        })
+    }
+
+    fn get(&mut self, name: &str) -> Option<&'a Attr> {
+        let i = self.item.attrs.iter().position(|a| a.name == name)?;
+        self.used[i] = true;
+        Some(&self.item.attrs[i])
+    }
+
+    /// An error at the attribute `name`, or the item if it's missing.
+    fn error(&self, name: &str, message: impl Into<String>) -> Error {
+        match self.item.attrs.iter().find(|a| a.name == name) {
+            Some(attr) => Error::new(attr.line, attr.col, message),
+            None => self.item.error(message),
+        }
+    }
+
+    fn number(&mut self, name: &str) -> Result<Option<f32>, Error> {
+        match self.get(name) {
+            Some(Attr {
+                value: Value::Number(n),
+                ..
+            }) => Ok(Some(*n)),
+            Some(attr) => Err(mismatch(attr, "a number")),
+            None => Ok(None),
+        }
+    }
+
+    fn vec3(&mut self, name: &str) -> Result<Option<Vec3>, Error> {
+        match self.get(name) {
+            Some(Attr {
+                value: Value::Vec3(v),
+                ..
+            }) => Ok(Some(*v)),
+            Some(attr) => Err(mismatch(attr, "a vector like `(x, y, z)`")),
+            None => Ok(None),
+        }
+    }
+
+    fn ident(&mut self, name: &str) -> Result<Option<String>, Error> {
+        match self.get(name) {
+            Some(Attr {
+                value: Value::Ident(s),
+                ..
+            }) => Ok(Some(s.clone())),
+            Some(attr) => Err(mismatch(attr, "a name")),
+            None => Ok(None),
+        }
+    }
+
+    fn string(&mut self, name: &str) -> Result<Option<String>, Error> {
+        match self.get(name) {
+            Some(Attr {
+                value: Value::Str(s),
+                ..
+            }) => Ok(Some(s.clone())),
+            Some(attr) => Err(mismatch(attr, "text in quotes")),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets an attribute with `f` that the item must have.
+    fn required<T>(
+        &mut self,
+        f: fn(&mut Self, &str) -> Result<Option<T>, Error>,
+        name: &str,
+    ) -> Result<T, Error> {
+        f(self, name)?.ok_or_else(|| {
+            self.item
+                .error(format!("`{}` is missing `{name}`", self.item.name))
+        })
+    }
+
+    /// Errors if any attributes weren't used.
+    fn finish(self) -> Result<(), Error> {
+        match self.used.iter().position(|used| !used) {
+            Some(i) => {
+                let attr = &self.item.attrs[i];
+                Err(Error::new(
+                    attr.line,
+                    attr.col,
+                    format!(
+                        "`{}` doesn't have an attribute `{}`",
+                        self.item.name, attr.name
+                    ),
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn mismatch(attr: &Attr, expected: &str) -> Error {
+    Error::new(
+        attr.line,
+        attr.col,
+        format!("expected `{}` to be {expected}", attr.name),
+    )
+}
@@ -0,0 +1,146 @@
+//! Splits the text of a scene into tokens.
+
+use super::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    Ident(String),
+    Number(f32),
+    /// Text inside of quotes, like `"sky.hdr"`.
+    Str(String),
+    /// `(`
+    LParen,
+    /// `)`
+    RParen,
+    /// `{`
+    LBrace,
+    /// `}`
+    RBrace,
+    /// `,`
+    Comma,
+    /// `=`
+    Equals,
+    /// The end of the text.
+    Eof,
+}
+
+/// A piece of the text, with the position it started at.
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub kind: Kind,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Splits `src` into tokens, the last token is always [`Kind::Eof`].
+///
+/// Whitespace and comments, from a `#` to the end of the line, are skipped.
+pub fn lex(src: &str) -> Result<Vec<Token>, Error> {
+    let mut cursor = Cursor {
+        chars: src.chars().collect(),
+        pos: 0,
+        line: 1,
+        col: 1,
+    };
+    let mut tokens = Vec::new();
+
+    while let Some(c) = cursor.peek() {
+        let (line, col) = (cursor.line, cursor.col);
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                cursor.bump();
+                continue;
+            }
+            '#' => {
+                cursor.eat_while(|c, _| c != '\n');
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                Kind::Ident(cursor.eat_while(|c, _| c.is_alphanumeric() || c == '_'))
+            }
+            c if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                // everything that could be part of a number, including exponents like `1e-3`
+                let s = cursor.eat_while(|c, prev| {
+                    c.is_ascii_alphanumeric()
+                        || c == '.'
+                        || (matches!(c, '-' | '+') && matches!(prev, None | Some('e' | 'E')))
+                });
+                // `inf` and `nan` parse, as do numbers too big for an `f32`, but none can be used
+                match s.parse::<f32>() {
+                    Ok(n) if n.is_finite() => Kind::Number(n),
+                    _ => return Err(Error::new(line, col, format!("invalid number `{s}`"))),
+                }
+            }
+            '"' => {
+                cursor.bump();
+                let s = cursor.eat_while(|c, _| c != '"' && c != '\n');
+                if cursor.peek() != Some('"') {
+                    return Err(Error::new(line, col, "unterminated string"));
+                }
+                cursor.bump();
+                Kind::Str(s)
+            }
+            _ => {
+                let kind = match c {
+                    '(' => Kind::LParen,
+                    ')' => Kind::RParen,
+                    '{' => Kind::LBrace,
+                    '}' => Kind::RBrace,
+                    ',' => Kind::Comma,
+                    '=' => Kind::Equals,
+                    _ => return Err(Error::new(line, col, format!("unexpected character `{c}`"))),
+                };
+                cursor.bump();
+                kind
+            }
+        };
+
+        tokens.push(Token { kind, line, col });
+    }
+
+    tokens.push(Token {
+        kind: Kind::Eof,
+        line: cursor.line,
+        col: cursor.col,
+    });
+    Ok(tokens)
+}
+
+/// Walks over the characters, keeping track of the line and column.
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+    // starting from 1, like text editors
+    line: usize,
+    col: usize,
+}
+
+impl Cursor {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) {
+        if self.peek() == Some('\n') {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        self.pos += 1;
+    }
+
+    /// Takes characters while `f(char, previous char taken)` is true.
+    fn eat_while(&mut self, f: impl Fn(char, Option<char>) -> bool) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.peek() {
+            if !f(c, s.chars().last()) {
+                break;
+            }
+            s.push(c);
+            self.bump();
+        }
+        s
+    }
+}
@@ -0,0 +1,116 @@
+//! Scenes described in a text file.
+//!
+//! A scene is a list of items, each with a name, some `name=value` attributes,
+//! and maybe some children inside of `{ }`.
+//! Values are numbers, names, text in quotes like `"sky.hdr"`, or vectors like `(x, y, z)`.
+//! Comments start with a `#` and go to the end of the line.
+//!
+//! ```text
+//! camera position=(0, 0, 2) target=(0, 0, 0) fov=50
+//! light direction=(1, 3, 1)
+//! light type=point position=(-1, 1, 1) color=(0.2, 0.3, 1) intensity=2
+//! environment type=sky turbidity=3
+//! march far=100 steps=128
+//!
+//! material name=pink color=(0.5, 0.2, 0.5)
+//! material name=grey color=(0.1, 0.1, 0.1)
+//!
+//! union smooth=0.2 material=pink {
+//!     sphere radius=0.5
+//!     translate offset=(0.5, 0, 0) {
+//!         box size=(0.3, 0.3, 0.3)
+//!     }
+//! }
+//! plane normal=(0, 1, 0) height=0.5 material=grey
+//! ```
+//!
+//! All of the objects at the top level are combined into one.
+//! See the README for every item and it's attributes.
+
+mod build;
+mod lex;
+mod parse;
+
+use std::fmt;
+use std::path::Path;
+
+use crate::camera::Projection;
+use crate::environment::Environment;
+use crate::fog::Fog;
+use crate::light::{self, Light};
+use crate::march::MarchSettings;
+use crate::material::Material;
+use crate::sdf::Node;
+use crate::vector::prelude::*;
+
+/// Where the camera is when the scene doesn't have one.
+pub const CAMERA: Vec3 = Vec3::new(0.0, 0.0, 2.0);
+/// The direction that's up for the camera when the scene doesn't say.
+pub const UP: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+/// The direction of the sun when the scene doesn't have any lights.
+pub const LIGHT: Vec3 = Vec3::new(1.0, 3.0, 1.0);
+
+/// Everything needed to render an image.
+pub struct Scene<S> {
+    /// Where the scene is viewed from.
+    pub camera: Projection,
+    /// The lights shining on the objects.
+    pub lights: Vec<Light>,
+    /// What's behind the objects, which lights them as well.
+    pub environment: Environment,
+    /// The air between the camera and the objects, if it's foggy.
+    pub fog: Option<Fog>,
+    /// How rays are marched through the objects.
+    pub march: MarchSettings,
+    /// Materials of the objects, indexed by their id.
+    pub materials: Vec<Material>,
+    /// The objects in the scene.
+    pub root: S,
+}
+
+/// Parses the text of a scene file.
+///
+/// Files the scene uses, like environment images, are found relative to `dir`.
+pub fn parse(src: &str, dir: &Path) -> Result<Scene<Node>, Error> {
+    let tokens = lex::lex(src)?;
+    let items = parse::parse(&tokens)?;
+    build::build(items, dir)
+}
+
+/// The direction towards the sun, the first directional light in `lights`.
+///
+/// The sky is lit by the sun, so they match up.
+pub fn sun(lights: &[Light]) -> Vec3 {
+    lights
+        .iter()
+        .find_map(|light| match light.kind {
+            light::Kind::Directional { dir } => Some(dir),
+            _ => None,
+        })
+        .unwrap_or(LIGHT)
+        .normalise()
+}
+
+/// Something wrong with a scene file, at a line and column.
+#[derive(Debug)]
+pub struct Error {
+    pub line: usize,
+    pub col: usize,
+    pub message: String,
+}
+
+impl Error {
+    fn new(line: usize, col: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            col,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
@@ -0,0 +1,177 @@
+//! Parses tokens into a tree of items.
+//!
+//! ```text
+//! scene := item*
+//! item  := IDENT (IDENT '=' value)* ('{' item* '}')?
+//! value := NUMBER | IDENT | STRING | '(' NUMBER ',' NUMBER ',' NUMBER ')'
+//! ```
+
+use super::lex::{Kind, Token};
+use super::Error;
+use crate::vector::prelude::*;
+
+/// Something in the scene, such as a shape or a light.
+#[derive(Debug)]
+pub struct Item {
+    pub name: String,
+    pub attrs: Vec<Attr>,
+    pub children: Vec<Item>,
+    pub line: usize,
+    pub col: usize,
+}
+
+/// An attribute of an [`Item`], `name=value`.
+#[derive(Debug)]
+pub struct Attr {
+    pub name: String,
+    pub value: Value,
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug)]
+pub enum Value {
+    Number(f32),
+    Ident(String),
+    Str(String),
+    Vec3(Vec3),
+}
+
+/// Parses all of the items in the `tokens`.
+pub fn parse(tokens: &[Token]) -> Result<Vec<Item>, Error> {
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut items = Vec::new();
+    while parser.peek().kind != Kind::Eof {
+        items.push(parser.item()?);
+    }
+    Ok(items)
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    pos: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> &'t Token {
+        // the last token is always Eof, so never step past it
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn next(&mut self) -> &'t Token {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    /// Takes the next token if it's the same kind as `kind`.
+    fn eat(&mut self, kind: &Kind) -> bool {
+        let matches = &self.peek().kind == kind;
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, kind: Kind, what: &str) -> Result<&'t Token, Error> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(unexpected(token, what))
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<(String, &'t Token), Error> {
+        let token = self.next();
+        match &token.kind {
+            Kind::Ident(s) => Ok((s.clone(), token)),
+            _ => Err(unexpected(token, what)),
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, Error> {
+        let token = self.next();
+        match token.kind {
+            Kind::Number(n) => Ok(n),
+            _ => Err(unexpected(token, "a number")),
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, Error> {
+        let (name, token) = self.ident("the name of an item")?;
+
+        let mut attrs = Vec::new();
+        // an identifier followed by `=` is an attribute, otherwise it's the next item
+        while let (Kind::Ident(_), Some(Kind::Equals)) = (
+            &self.peek().kind,
+            self.tokens.get(self.pos + 1).map(|t| &t.kind),
+        ) {
+            let (name, token) = self.ident("an attribute")?;
+            self.expect(Kind::Equals, "`=`")?;
+            attrs.push(Attr {
+                name,
+                value: self.value()?,
+                line: token.line,
+                col: token.col,
+            });
+        }
+
+        let mut children = Vec::new();
+        if self.eat(&Kind::LBrace) {
+            while !self.eat(&Kind::RBrace) {
+                if self.peek().kind == Kind::Eof {
+                    return Err(unexpected(self.peek(), "`}`"));
+                }
+                children.push(self.item()?);
+            }
+        }
+
+        Ok(Item {
+            name,
+            attrs,
+            children,
+            line: token.line,
+            col: token.col,
+        })
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        let token = self.next();
+        match &token.kind {
+            Kind::Number(n) => Ok(Value::Number(*n)),
+            Kind::Ident(s) => Ok(Value::Ident(s.clone())),
+            Kind::Str(s) => Ok(Value::Str(s.clone())),
+            Kind::LParen => {
+                let x = self.number()?;
+                self.expect(Kind::Comma, "`,`")?;
+                let y = self.number()?;
+                self.expect(Kind::Comma, "`,`")?;
+                let z = self.number()?;
+                self.expect(Kind::RParen, "`)`")?;
+                Ok(Value::Vec3(vec3(x, y, z)))
+            }
+            _ => Err(unexpected(token, "a value")),
+        }
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> Error {
+    let found = match &token.kind {
+        Kind::Ident(s) => format!("`{s}`"),
+        Kind::Number(n) => format!("`{n}`"),
+        Kind::Str(s) => format!("`\"{s}\"`"),
+        Kind::LParen => "`(`".into(),
+        Kind::RParen => "`)`".into(),
+        Kind::LBrace => "`{`".into(),
+        Kind::RBrace => "`}`".into(),
+        Kind::Comma => "`,`".into(),
+        Kind::Equals => "`=`".into(),
+        Kind::Eof => "the end of the file".into(),
+    };
+    Error::new(
+        token.line,
+        token.col,
+        format!("expected {expected}, found {found}"),
+    )
+}
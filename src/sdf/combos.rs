@@ -0,0 +1,357 @@
+//! Combinations for static shapes.
+
+use super::*;
+
+/// Translates an Sdf by the given vector.
+pub struct Translate<V: Comp<3>, S: Sdf<V>>(pub(super) S, pub(super) V::Vec);
+
+impl<V: Comp<3>, S: Sdf<V>> Sdf<V> for Translate<V, S> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // shift the input vector by the given vector.
+        self.0.dist(p - self.1)
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>> MaterialSdf<V> for Translate<V, S> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        self.0.dist_mat(p - self.1)
+    }
+}
+
+/// Gives an Sdf a material id.
+pub struct WithMaterial<V: Comp<3>, S: Sdf<V>>(pub(super) S, pub(super) V);
+
+impl<V: Comp<3>, S: Sdf<V>> Sdf<V> for WithMaterial<V, S> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        self.0.dist(p)
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>> MaterialSdf<V> for WithMaterial<V, S> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        (self.0.dist(p), self.1)
+    }
+}
+
+/// Rotates an Sdf by the given rotation matrix.
+pub struct Rotate<V: Comp<3>, S: Sdf<V>>(S, Mat3<V>);
+
+impl<V: Comp<3>, S: Sdf<V>> Rotate<V, S> {
+    pub(super) fn new(s: S, rotation: Mat3<V>) -> Self {
+        // the inverse of a rotation is it's transpose
+        Self(s, rotation.transpose())
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>> Sdf<V> for Rotate<V, S> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // rotating the input point the opposite way rotates the surface.
+        self.0.dist(self.1.mul_vec3(p))
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>> MaterialSdf<V> for Rotate<V, S> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        self.0.dist_mat(self.1.mul_vec3(p))
+    }
+}
+
+/// Uniformly scales an Sdf by the given factor.
+pub struct Scale<V: Comp<3>, S: Sdf<V>>(pub(super) S, pub(super) V);
+
+impl<V: Comp<3>, S: Sdf<V>> Sdf<V> for Scale<V, S> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // the distance is measured in the scaled space, so has to be scaled back.
+        self.0.dist(p / self.1) * self.1
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>> MaterialSdf<V> for Scale<V, S> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (d, id) = self.0.dist_mat(p / self.1);
+        (d * self.1, id)
+    }
+}
+
+/// Transforms an Sdf by the given affine matrix.
+pub struct Transform<V: Comp<3>, S: Sdf<V>> {
+    s: S,
+    inv: Mat4<V>,
+    scale: V,
+}
+
+impl<V: Comp<3>, S: Sdf<V>> Transform<V, S> {
+    pub(super) fn new(s: S, mat: Mat4<V>) -> Self {
+        let [x, y, z, _] = mat.x_axis;
+        Self {
+            s,
+            inv: mat.inverse(),
+            // length of an axis is the scale, assuming it's uniform
+            scale: (x * x + y * y + z * z).sqrt(),
+        }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>> Sdf<V> for Transform<V, S> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        self.s.dist(self.inv.transform_point3(p)) * self.scale
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>> MaterialSdf<V> for Transform<V, S> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (d, id) = self.s.dist_mat(self.inv.transform_point3(p));
+        (d * self.scale, id)
+    }
+}
+
+/// The union of two Sdfs.
+pub struct Union<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> {
+    a: S,
+    b: U,
+    // we don't own a V, it's just for type checking
+    _v: core::marker::PhantomData<fn() -> V>,
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Union<V, S, U> {
+    pub(super) fn new(a: S, b: U) -> Self {
+        Self {
+            a,
+            b,
+            _v: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Sdf<V> for Union<V, S, U> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // return the value of the cloesest sdf.
+        self.a.dist(p).min(self.b.dist(p))
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>, U: MaterialSdf<V>> MaterialSdf<V> for Union<V, S, U> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (a, a_id) = self.a.dist_mat(p);
+        let (b, b_id) = self.b.dist_mat(p);
+        // the closest surface is the one we see.
+        let closest = a.simd_lt(b);
+        (closest.select(a, b), closest.select(a_id, b_id))
+    }
+}
+
+/// The intersection of two Sdfs.
+pub struct Intersection<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> {
+    a: S,
+    b: U,
+    // we don't own a V, it's just for type checking
+    _v: core::marker::PhantomData<fn() -> V>,
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Intersection<V, S, U> {
+    pub(super) fn new(a: S, b: U) -> Self {
+        Self {
+            a,
+            b,
+            _v: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Sdf<V> for Intersection<V, S, U> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // return the value of the furthest sdf.
+        self.a.dist(p).max(self.b.dist(p))
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>, U: MaterialSdf<V>> MaterialSdf<V> for Intersection<V, S, U> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (a, a_id) = self.a.dist_mat(p);
+        let (b, b_id) = self.b.dist_mat(p);
+        // the furthest surface is the one we see.
+        let furthest = a.simd_gt(b);
+        (furthest.select(a, b), furthest.select(a_id, b_id))
+    }
+}
+
+/// The subtraction of the second Sdf from the first.
+pub struct Subtraction<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> {
+    a: S,
+    b: U,
+    // we don't own a V, it's just for type checking
+    _v: core::marker::PhantomData<fn() -> V>,
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Subtraction<V, S, U> {
+    pub(super) fn new(a: S, b: U) -> Self {
+        Self {
+            a,
+            b,
+            _v: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Sdf<V> for Subtraction<V, S, U> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // the inside of `b` becomes the outside.
+        self.a.dist(p).max(-self.b.dist(p))
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>, U: MaterialSdf<V>> MaterialSdf<V> for Subtraction<V, S, U> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (a, a_id) = self.a.dist_mat(p);
+        let (b, b_id) = self.b.dist_mat(p);
+        // where `b` is cut out, it's surface is the one we see.
+        let cut = (-b).simd_gt(a);
+        (cut.select(-b, a), cut.select(b_id, a_id))
+    }
+}
+
+/// The smooth union of two Sdfs.
+pub struct SmoothUnion<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> {
+    a: S,
+    b: U,
+    k: V,
+    blend: Blend,
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> SmoothUnion<V, S, U> {
+    pub(super) fn new(a: S, b: U, k: V) -> Self {
+        Self {
+            a,
+            b,
+            k,
+            blend: Blend::default(),
+        }
+    }
+
+    /// Changes the curve used to blend the two Sdfs.
+    pub fn with_blend(self, blend: Blend) -> Self {
+        Self { blend, ..self }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Sdf<V> for SmoothUnion<V, S, U> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        self.blend.min(self.a.dist(p), self.b.dist(p), self.k)
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>, U: MaterialSdf<V>> MaterialSdf<V> for SmoothUnion<V, S, U> {
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (a, a_id) = self.a.dist_mat(p);
+        let (b, b_id) = self.b.dist_mat(p);
+        // use the material of the closest surface.
+        let closest = a.simd_lt(b);
+        (self.blend.min(a, b, self.k), closest.select(a_id, b_id))
+    }
+}
+
+/// The smooth intersection of two Sdfs.
+pub struct SmoothIntersection<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> {
+    a: S,
+    b: U,
+    k: V,
+    blend: Blend,
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> SmoothIntersection<V, S, U> {
+    pub(super) fn new(a: S, b: U, k: V) -> Self {
+        Self {
+            a,
+            b,
+            k,
+            blend: Blend::default(),
+        }
+    }
+
+    /// Changes the curve used to blend the two Sdfs.
+    pub fn with_blend(self, blend: Blend) -> Self {
+        Self { blend, ..self }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Sdf<V> for SmoothIntersection<V, S, U> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        self.blend.max(self.a.dist(p), self.b.dist(p), self.k)
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>, U: MaterialSdf<V>> MaterialSdf<V>
+    for SmoothIntersection<V, S, U>
+{
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (a, a_id) = self.a.dist_mat(p);
+        let (b, b_id) = self.b.dist_mat(p);
+        // use the material of the furthest surface.
+        let furthest = a.simd_gt(b);
+        (self.blend.max(a, b, self.k), furthest.select(a_id, b_id))
+    }
+}
+
+/// The smooth subtraction of the second Sdf from the first.
+pub struct SmoothSubtraction<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> {
+    a: S,
+    b: U,
+    k: V,
+    blend: Blend,
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> SmoothSubtraction<V, S, U> {
+    pub(super) fn new(a: S, b: U, k: V) -> Self {
+        Self {
+            a,
+            b,
+            k,
+            blend: Blend::default(),
+        }
+    }
+
+    /// Changes the curve used to blend the two Sdfs.
+    pub fn with_blend(self, blend: Blend) -> Self {
+        Self { blend, ..self }
+    }
+}
+
+impl<V: Comp<3>, S: Sdf<V>, U: Sdf<V>> Sdf<V> for SmoothSubtraction<V, S, U> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        self.blend.max(self.a.dist(p), -self.b.dist(p), self.k)
+    }
+}
+
+impl<V: Comp<3>, S: MaterialSdf<V>, U: MaterialSdf<V>> MaterialSdf<V>
+    for SmoothSubtraction<V, S, U>
+{
+    #[inline]
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        let (a, a_id) = self.a.dist_mat(p);
+        let (b, b_id) = self.b.dist_mat(p);
+        // where `b` is cut out, use it's material.
+        let cut = (-b).simd_gt(a);
+        (self.blend.max(a, -b, self.k), cut.select(b_id, a_id))
+    }
+}
@@ -0,0 +1,139 @@
+#![allow(dead_code)]
+
+use crate::vector::prelude::*;
+
+/// Write `use sdf::prelude::*` to easily import useful traits.
+pub mod prelude {
+    pub use super::Blend;
+    pub use super::MaterialSdf;
+    pub use super::Sdf;
+    pub use super::SdfExt;
+}
+
+/// An object that has a Signed Distance Function.
+pub trait Sdf<V: Comp<3>> {
+    /// The distance of the surface from point `p` in 3D space.
+    fn dist(&self, p: V::Vec) -> V;
+}
+
+/// An Sdf that knows which material it's surface is made of.
+pub trait MaterialSdf<V: Comp<3>>: Sdf<V> {
+    /// The distance of the surface from point `p`, and the id of the material at that surface.
+    fn dist_mat(&self, p: V::Vec) -> (V, V);
+}
+
+/// Extensions for modifiying Signed Distance Fields.
+pub trait SdfExt<V: Comp<3>>: Sdf<V> + Sized {
+    /// Translates the Sdf by `pos`.
+    #[inline]
+    fn translate(self, pos: V::Vec) -> Translate<V, Self> {
+        Translate(self, pos)
+    }
+
+    /// Gives the surface of the Sdf the material with `id`.
+    #[inline]
+    fn material(self, id: u32) -> WithMaterial<V, Self> {
+        WithMaterial(self, V::from(id as f32))
+    }
+
+    /// Rotates the Sdf by `angle` radians around `axis`.
+    #[inline]
+    fn rotate(self, axis: V::Vec, angle: V) -> Rotate<V, Self> {
+        Rotate::new(self, Mat3::from_axis_angle(axis, angle))
+    }
+
+    /// Uniformly scales the Sdf by `s`.
+    #[inline]
+    fn scale(self, s: V) -> Scale<V, Self> {
+        Scale(self, s)
+    }
+
+    /// Transforms the Sdf by the rigid transformation `mat`.
+    ///
+    /// A uniform scale in `mat` is also allowed, the distance is corrected for it.
+    #[inline]
+    fn transform(self, mat: Mat4<V>) -> Transform<V, Self> {
+        Transform::new(self, mat)
+    }
+
+    /// Combines two Sdf together.
+    #[inline]
+    fn union<U: Sdf<V>>(self, other: U) -> Union<V, Self, U> {
+        Union::new(self, other)
+    }
+
+    /// The region where both Sdfs overlap.
+    #[inline]
+    fn intersect<U: Sdf<V>>(self, other: U) -> Intersection<V, Self, U> {
+        Intersection::new(self, other)
+    }
+
+    /// Cuts `other` out of the Sdf.
+    #[inline]
+    fn subtract<U: Sdf<V>>(self, other: U) -> Subtraction<V, Self, U> {
+        Subtraction::new(self, other)
+    }
+
+    /// Combines two Sdf together, blending the seam over a radius of `k`.
+    #[inline]
+    fn smooth_union<U: Sdf<V>>(self, other: U, k: V) -> SmoothUnion<V, Self, U> {
+        SmoothUnion::new(self, other, k)
+    }
+
+    /// The region where both Sdfs overlap, blending the seam over a radius of `k`.
+    #[inline]
+    fn smooth_intersect<U: Sdf<V>>(self, other: U, k: V) -> SmoothIntersection<V, Self, U> {
+        SmoothIntersection::new(self, other, k)
+    }
+
+    /// Cuts `other` out of the Sdf, blending the seam over a radius of `k`.
+    #[inline]
+    fn smooth_subtract<U: Sdf<V>>(self, other: U, k: V) -> SmoothSubtraction<V, Self, U> {
+        SmoothSubtraction::new(self, other, k)
+    }
+}
+
+impl<V: Comp<3>, T: Sdf<V> + Sized> SdfExt<V> for T {}
+
+mod combos;
+mod node;
+mod shapes;
+
+pub use combos::*;
+pub use node::*;
+pub use shapes::*;
+
+/// The curve used to blend two distances together in the smooth combinators.
+///
+/// https://iquilezles.org/articles/smin/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Blend {
+    /// Quadratic polynomial, only affects distances within `k` of each other.
+    #[default]
+    Polynomial,
+    /// Exponential, smoother but affects the entire field.
+    Exponential,
+}
+
+impl Blend {
+    /// The smooth minimum of `a` and `b` with a blend radius of `k`.
+    #[inline]
+    pub fn min<V: Comp<3>>(self, a: V, b: V, k: V) -> V {
+        match self {
+            Blend::Polynomial => {
+                let h = (k - (a - b).abs()).max(V::ZERO) / k;
+                a.min(b) - h * h * k * V::from(0.25)
+            }
+            Blend::Exponential => {
+                let r = (-a / k).exp() + (-b / k).exp();
+                -k * r.ln()
+            }
+        }
+    }
+
+    /// The smooth maximum of `a` and `b` with a blend radius of `k`.
+    #[inline]
+    pub fn max<V: Comp<3>>(self, a: V, b: V, k: V) -> V {
+        -self.min(-a, -b, k)
+    }
+}
@@ -0,0 +1,434 @@
+//! A tree of Sdfs that is built at runtime.
+
+use std::simd::f32x8;
+
+use super::shapes::*;
+use super::{Blend, MaterialSdf, Sdf};
+use crate::vector::prelude::*;
+
+/// A node in a tree of Sdfs that is built at runtime, such as from a scene file.
+///
+/// All parameters are stored as scalars and splat when evaluated,
+/// so the same tree can be rendered with either `f32` or `f32x8`.
+///
+/// Surfaces outside of a [`Node::Material`] use the material with id `0`.
+///
+/// # Example
+/// ```
+/// let scene = Node::union(vec![
+///     Node::from(Sphere(0.5)).named("ball"),
+///     Node::from(Plane { normal: vec3(0.0, 1.0, 0.0), height: 0.5 }).with_material(1),
+/// ]);
+/// ```
+pub enum Node {
+    /// A single primitive shape.
+    Shape(Shape),
+    /// Combines all of the children with the [`Op`].
+    Combine {
+        op: Op,
+        children: Vec<Node>,
+        /// Blend radius and curve, `None` combines them sharply.
+        smooth: Option<(f32, Blend)>,
+    },
+    /// Translates the child by the vector.
+    Translate(Vec3, std::boxed::Box<Node>),
+    /// Rotates the child by the inverse of the matrix.
+    Rotate(Mat3<f32>, std::boxed::Box<Node>),
+    /// Uniformly scales the child.
+    Scale(f32, std::boxed::Box<Node>),
+    /// Transforms the child by the inverse of the matrix, correcting the distance by the scale.
+    Transform(Mat4<f32>, f32, std::boxed::Box<Node>),
+    /// Gives the child, and any of its children without a material of their own, the material with the id.
+    Material(u32, std::boxed::Box<Node>),
+    /// Gives the child a name, so it can be found with [`Node::find`].
+    Named(String, std::boxed::Box<Node>),
+    /// Any other Sdf, such as one of the static combinators.
+    Dyn(std::boxed::Box<dyn DynSdf>),
+}
+
+/// How the children of a [`Node::Combine`] are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Union,
+    Intersection,
+    /// Subtracts the rest of the children from the first.
+    Subtraction,
+}
+
+/// A primitive shape from [`super::shapes`].
+pub enum Shape {
+    Sphere(Sphere<f32>),
+    Box(Box<f32>),
+    Plane(Plane<f32>),
+    Torus(Torus<f32>),
+    CappedCylinder(CappedCylinder<f32>),
+    Capsule(Capsule<f32>),
+    Cone(Cone<f32>),
+    Ellipsoid(Ellipsoid<f32>),
+    RoundBox(RoundBox<f32>),
+    Octahedron(Octahedron<f32>),
+    HexPrism(HexPrism<f32>),
+    Triangle(Triangle<f32>),
+    Quad(Quad<f32>),
+}
+
+impl Node {
+    /// Combines all of the `children` together.
+    pub fn union(children: Vec<Node>) -> Self {
+        Self::Combine {
+            op: Op::Union,
+            children,
+            smooth: None,
+        }
+    }
+
+    /// The region where all of the `children` overlap.
+    pub fn intersection(children: Vec<Node>) -> Self {
+        Self::Combine {
+            op: Op::Intersection,
+            children,
+            smooth: None,
+        }
+    }
+
+    /// Cuts the rest of the `children` out of the first.
+    pub fn subtraction(children: Vec<Node>) -> Self {
+        Self::Combine {
+            op: Op::Subtraction,
+            children,
+            smooth: None,
+        }
+    }
+
+    /// Stores an Sdf that has both a scalar and a SIMD version, `(Sdf<f32>, Sdf<f32x8>)`.
+    ///
+    /// The scalar version can usually be widened into the SIMD one.
+    pub fn dynamic(sdf: impl DynSdf + 'static) -> Self {
+        Self::Dyn(std::boxed::Box::new(sdf))
+    }
+
+    /// Gives the node a `name`.
+    #[must_use]
+    pub fn named(self, name: impl Into<String>) -> Self {
+        Self::Named(name.into(), std::boxed::Box::new(self))
+    }
+
+    /// Gives the node the material with `id`.
+    #[must_use]
+    pub fn with_material(self, id: u32) -> Self {
+        Self::Material(id, std::boxed::Box::new(self))
+    }
+
+    /// Translates the node by `offset`.
+    #[must_use]
+    pub fn translated(self, offset: Vec3) -> Self {
+        Self::Translate(offset, std::boxed::Box::new(self))
+    }
+
+    /// Uniformly scales the node by `s`.
+    #[must_use]
+    pub fn scaled(self, s: f32) -> Self {
+        Self::Scale(s, std::boxed::Box::new(self))
+    }
+
+    /// Rotates the child by `angle` radians around `axis`.
+    pub fn rotate(child: Node, axis: Vec3, angle: f32) -> Self {
+        // the inverse of a rotation is it's transpose
+        let m = Mat3::from_axis_angle(axis, angle).transpose();
+        Self::Rotate(m, std::boxed::Box::new(child))
+    }
+
+    /// Transforms the child by the rigid transformation `mat`, which may contain a uniform scale.
+    pub fn transform(child: Node, mat: Mat4<f32>) -> Self {
+        let [x, y, z, _] = mat.x_axis;
+        let scale = (x * x + y * y + z * z).sqrt();
+        Self::Transform(mat.inverse(), scale, std::boxed::Box::new(child))
+    }
+
+    /// The nodes directly below this one.
+    pub fn children(&self) -> &[Node] {
+        match self {
+            Node::Shape(_) | Node::Dyn(_) => &[],
+            Node::Combine { children, .. } => children,
+            Node::Translate(_, child)
+            | Node::Rotate(_, child)
+            | Node::Scale(_, child)
+            | Node::Transform(_, _, child)
+            | Node::Material(_, child)
+            | Node::Named(_, child) => core::slice::from_ref(child),
+        }
+    }
+
+    /// The nodes directly below this one, which can be changed.
+    pub fn children_mut(&mut self) -> &mut [Node] {
+        match self {
+            Node::Shape(_) | Node::Dyn(_) => &mut [],
+            Node::Combine { children, .. } => children,
+            Node::Translate(_, child)
+            | Node::Rotate(_, child)
+            | Node::Scale(_, child)
+            | Node::Transform(_, _, child)
+            | Node::Material(_, child)
+            | Node::Named(_, child) => core::slice::from_mut(child),
+        }
+    }
+
+    /// Finds the first node with `name`, searching depth first.
+    ///
+    /// The [`Node::Named`] is returned, so it's transforms can be changed.
+    pub fn find(&self, name: &str) -> Option<&Node> {
+        match self {
+            Node::Named(n, _) if n == name => Some(self),
+            _ => self.children().iter().find_map(|c| c.find(name)),
+        }
+    }
+
+    /// Finds the first node with `name`, searching depth first.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Node> {
+        if matches!(self, Node::Named(n, _) if n == name) {
+            return Some(self);
+        }
+        self.children_mut()
+            .iter_mut()
+            .find_map(|c| c.find_mut(name))
+    }
+}
+
+/// An Sdf that can be stored in a [`Node`].
+///
+/// A `Node` can be rendered with either type of vector,
+/// so this is implemented for a pair of the same Sdf, `(Sdf<f32>, Sdf<f32x8>)`.
+pub trait DynSdf: Send + Sync {
+    fn dist_mat_f32(&self, p: Vec3) -> (f32, f32);
+    fn dist_mat_f32x8(&self, p: Vec3x8) -> (f32x8, f32x8);
+}
+
+impl<A, B> DynSdf for (A, B)
+where
+    A: MaterialSdf<f32> + Send + Sync,
+    B: MaterialSdf<f32x8> + Send + Sync,
+{
+    #[inline]
+    fn dist_mat_f32(&self, p: Vec3) -> (f32, f32) {
+        self.0.dist_mat(p)
+    }
+
+    #[inline]
+    fn dist_mat_f32x8(&self, p: Vec3x8) -> (f32x8, f32x8) {
+        self.1.dist_mat(p)
+    }
+}
+
+/// Components that a [`DynSdf`] can be evaluated with.
+pub trait DynComp: Comp<3> {
+    fn dist_mat(sdf: &dyn DynSdf, p: Self::Vec) -> (Self, Self);
+}
+
+impl DynComp for f32 {
+    #[inline]
+    fn dist_mat(sdf: &dyn DynSdf, p: Vec3) -> (f32, f32) {
+        sdf.dist_mat_f32(p)
+    }
+}
+
+impl DynComp for f32x8 {
+    #[inline]
+    fn dist_mat(sdf: &dyn DynSdf, p: Vec3x8) -> (f32x8, f32x8) {
+        sdf.dist_mat_f32x8(p)
+    }
+}
+
+macro_rules! impl_from_shape {
+    ($($shape:ident),*) => {
+        $(
+            impl From<$shape<f32>> for Node {
+                fn from(shape: $shape<f32>) -> Self {
+                    Node::Shape(Shape::$shape(shape))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_shape!(
+    Sphere,
+    Box,
+    Plane,
+    Torus,
+    CappedCylinder,
+    Capsule,
+    Cone,
+    Ellipsoid,
+    RoundBox,
+    Octahedron,
+    HexPrism,
+    Triangle,
+    Quad
+);
+
+impl<V: DynComp> Sdf<V> for Node {
+    fn dist(&self, p: V::Vec) -> V {
+        match self {
+            Node::Shape(shape) => Sdf::<V>::dist(shape, p),
+            Node::Combine {
+                op,
+                children,
+                smooth,
+            } => {
+                let mut dists = children.iter().map(|c| Sdf::<V>::dist(c, p));
+                let first = dists.next().unwrap_or_else(|| V::from(f32::MAX));
+                dists.fold(first, |a, b| combine(*op, *smooth, a, b))
+            }
+            Node::Translate(offset, child) => Sdf::<V>::dist(&**child, p - from_vec3::<V>(*offset)),
+            Node::Rotate(m, child) => Sdf::<V>::dist(&**child, splat_mat3::<V>(m).mul_vec3(p)),
+            Node::Scale(s, child) => {
+                let s = V::from(*s);
+                Sdf::<V>::dist(&**child, p / s) * s
+            }
+            Node::Transform(inv, scale, child) => {
+                Sdf::<V>::dist(&**child, splat_mat4::<V>(inv).transform_point3(p)) * V::from(*scale)
+            }
+            Node::Material(_, child) | Node::Named(_, child) => Sdf::<V>::dist(&**child, p),
+            Node::Dyn(sdf) => V::dist_mat(&**sdf, p).0,
+        }
+    }
+}
+
+impl<V: DynComp> MaterialSdf<V> for Node {
+    fn dist_mat(&self, p: V::Vec) -> (V, V) {
+        // objects without a material use the first one
+        self.dist_mat_or(p, V::ZERO)
+    }
+}
+
+impl Node {
+    /// [`MaterialSdf::dist_mat`], where objects without a material of their own have the material `id`.
+    fn dist_mat_or<V: DynComp>(&self, p: V::Vec, id: V) -> (V, V) {
+        match self {
+            Node::Shape(shape) => (Sdf::<V>::dist(shape, p), id),
+            Node::Combine {
+                op,
+                children,
+                smooth,
+            } => {
+                let mut dists = children.iter().map(|c| c.dist_mat_or(p, id));
+                let first = dists.next().unwrap_or_else(|| (V::from(f32::MAX), id));
+                dists.fold(first, |(a, a_id), (b, b_id)| {
+                    // the id of the surface that is seen after combining
+                    let id = match op {
+                        Op::Union => a.simd_lt(b).select(a_id, b_id),
+                        Op::Intersection => a.simd_gt(b).select(a_id, b_id),
+                        Op::Subtraction => (-b).simd_gt(a).select(b_id, a_id),
+                    };
+                    (combine(*op, *smooth, a, b), id)
+                })
+            }
+            Node::Translate(offset, child) => child.dist_mat_or(p - from_vec3::<V>(*offset), id),
+            Node::Rotate(m, child) => child.dist_mat_or(splat_mat3::<V>(m).mul_vec3(p), id),
+            Node::Scale(s, child) => {
+                let s = V::from(*s);
+                let (d, id) = child.dist_mat_or(p / s, id);
+                (d * s, id)
+            }
+            Node::Transform(inv, scale, child) => {
+                let (d, id) = child.dist_mat_or(splat_mat4::<V>(inv).transform_point3(p), id);
+                (d * V::from(*scale), id)
+            }
+            // the material is used by everything inside that doesn't have one
+            Node::Material(own, child) => child.dist_mat_or(p, V::from(*own as f32)),
+            Node::Named(_, child) => child.dist_mat_or(p, id),
+            Node::Dyn(sdf) => V::dist_mat(&**sdf, p),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Shape {
+    fn dist(&self, p: V::Vec) -> V {
+        let f = |x: f32| V::from(x);
+        let v = |v: Vec3| from_vec3::<V>(v);
+        match self {
+            Shape::Sphere(s) => Sphere(f(s.0)).dist(p),
+            Shape::Box(s) => Box(v(s.0)).dist(p),
+            Shape::Plane(s) => Plane {
+                normal: v(s.normal),
+                height: f(s.height),
+            }
+            .dist(p),
+            Shape::Torus(s) => Torus {
+                major: f(s.major),
+                minor: f(s.minor),
+            }
+            .dist(p),
+            Shape::CappedCylinder(s) => CappedCylinder {
+                radius: f(s.radius),
+                height: f(s.height),
+            }
+            .dist(p),
+            Shape::Capsule(s) => Capsule {
+                a: v(s.a),
+                b: v(s.b),
+                radius: f(s.radius),
+            }
+            .dist(p),
+            Shape::Cone(s) => Cone {
+                sin: f(s.sin),
+                cos: f(s.cos),
+                height: f(s.height),
+            }
+            .dist(p),
+            Shape::Ellipsoid(s) => Ellipsoid(v(s.0)).dist(p),
+            Shape::RoundBox(s) => RoundBox {
+                size: v(s.size),
+                radius: f(s.radius),
+            }
+            .dist(p),
+            Shape::Octahedron(s) => Octahedron(f(s.0)).dist(p),
+            Shape::HexPrism(s) => HexPrism {
+                radius: f(s.radius),
+                height: f(s.height),
+            }
+            .dist(p),
+            Shape::Triangle(s) => Triangle {
+                a: v(s.a),
+                b: v(s.b),
+                c: v(s.c),
+            }
+            .dist(p),
+            Shape::Quad(s) => Quad {
+                a: v(s.a),
+                b: v(s.b),
+                c: v(s.c),
+                d: v(s.d),
+            }
+            .dist(p),
+        }
+    }
+}
+
+/// Combines two distances with the [`Op`].
+#[inline]
+fn combine<V: Comp<3>>(op: Op, smooth: Option<(f32, Blend)>, a: V, b: V) -> V {
+    match (op, smooth) {
+        (Op::Union, None) => a.min(b),
+        (Op::Intersection, None) => a.max(b),
+        (Op::Subtraction, None) => a.max(-b),
+        (Op::Union, Some((k, blend))) => blend.min(a, b, V::from(k)),
+        (Op::Intersection, Some((k, blend))) => blend.max(a, b, V::from(k)),
+        (Op::Subtraction, Some((k, blend))) => blend.max(a, -b, V::from(k)),
+    }
+}
+
+#[inline]
+fn splat_mat3<V: Comp<3>>(m: &Mat3<f32>) -> Mat3<V> {
+    Mat3::from_cols(
+        from_vec3::<V>(m.x_axis),
+        from_vec3::<V>(m.y_axis),
+        from_vec3::<V>(m.z_axis),
+    )
+}
+
+#[inline]
+fn splat_mat4<V: Comp<3>>(m: &Mat4<f32>) -> Mat4<V> {
+    let col = |c: [f32; 4]| c.map(V::from);
+    Mat4::from_cols(col(m.x_axis), col(m.y_axis), col(m.z_axis), col(m.w_axis))
+}
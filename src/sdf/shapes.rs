@@ -0,0 +1,395 @@
+//! Static shapes and their SDF functions.
+
+use std::simd::f32x8;
+
+use super::*;
+
+/// A [`Sphere`] with a given radius.
+pub struct Sphere<V: Comp<3>>(pub V);
+
+impl Sphere<f32> {
+    pub fn widen(self) -> Sphere<f32x8> {
+        Sphere(f32x8::splat(self.0))
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Sphere<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        p.length() - self.0
+    }
+}
+
+/// A [`Box`] with radii for each dimension.
+pub struct Box<V: Comp<3>>(pub V::Vec);
+
+impl Box<f32> {
+    pub fn widen(self) -> Box<f32x8> {
+        Box(self.0.widen())
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Box<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let q = p.abs() - self.0;
+        q.max(V::Vec::ZERO).length() + q.max_element().min(V::ZERO)
+    }
+}
+
+/// An infinite [`Plane`] with a normalised `normal`, offset from the origin by `height`.
+pub struct Plane<V: Comp<3>> {
+    pub normal: V::Vec,
+    pub height: V,
+}
+
+impl Plane<f32> {
+    pub fn widen(self) -> Plane<f32x8> {
+        Plane {
+            normal: self.normal.widen(),
+            height: f32x8::splat(self.height),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Plane<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        p.dot(self.normal) + self.height
+    }
+}
+
+/// A [`Torus`] lying in the XZ plane.
+pub struct Torus<V: Comp<3>> {
+    /// Distance from the center to the middle of the tube.
+    pub major: V,
+    /// Radius of the tube.
+    pub minor: V,
+}
+
+impl Torus<f32> {
+    pub fn widen(self) -> Torus<f32x8> {
+        Torus {
+            major: f32x8::splat(self.major),
+            minor: f32x8::splat(self.minor),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Torus<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let [x, y, z] = p.to_array();
+        length2(length2(x, z) - self.major, y) - self.minor
+    }
+}
+
+/// A [`CappedCylinder`] standing along the Y axis.
+pub struct CappedCylinder<V: Comp<3>> {
+    pub radius: V,
+    /// Half of the total height.
+    pub height: V,
+}
+
+impl CappedCylinder<f32> {
+    pub fn widen(self) -> CappedCylinder<f32x8> {
+        CappedCylinder {
+            radius: f32x8::splat(self.radius),
+            height: f32x8::splat(self.height),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for CappedCylinder<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let [x, y, z] = p.to_array();
+        let dx = length2(x, z) - self.radius;
+        let dy = y.abs() - self.height;
+        dx.max(dy).min(V::ZERO) + length2(dx.max(V::ZERO), dy.max(V::ZERO))
+    }
+}
+
+/// A [`Capsule`] (line segment with a radius) between points `a` and `b`.
+pub struct Capsule<V: Comp<3>> {
+    pub a: V::Vec,
+    pub b: V::Vec,
+    pub radius: V,
+}
+
+impl Capsule<f32> {
+    pub fn widen(self) -> Capsule<f32x8> {
+        Capsule {
+            a: self.a.widen(),
+            b: self.b.widen(),
+            radius: f32x8::splat(self.radius),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Capsule<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let pa = p - self.a;
+        let ba = self.b - self.a;
+        // closest point along the segment
+        let h = (pa.dot(ba) / ba.dot(ba)).clamp(V::ZERO, V::ONE);
+        (pa - ba * h).length() - self.radius
+    }
+}
+
+/// A [`Cone`] with it's tip at the origin, opening downwards along the Y axis.
+pub struct Cone<V: Comp<3>> {
+    pub(super) sin: V,
+    pub(super) cos: V,
+    pub(super) height: V,
+}
+
+impl<V: Comp<3>> Cone<V> {
+    /// Creates a new [`Cone`] where `angle` (in radians) is measured from the axis to the side.
+    pub fn new(angle: V, height: V) -> Self {
+        Self {
+            sin: angle.sin(),
+            cos: angle.cos(),
+            height,
+        }
+    }
+}
+
+impl Cone<f32> {
+    pub fn widen(self) -> Cone<f32x8> {
+        Cone {
+            sin: f32x8::splat(self.sin),
+            cos: f32x8::splat(self.cos),
+            height: f32x8::splat(self.height),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Cone<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // everything is calculated in the 2D plane containing the axis and `p`
+        let [x, y, z] = p.to_array();
+        let (qx, qy) = (self.height * self.sin / self.cos, -self.height);
+        let (wx, wy) = (length2(x, z), y);
+        // closest point on the side
+        let t = ((wx * qx + wy * qy) / (qx * qx + qy * qy)).clamp(V::ZERO, V::ONE);
+        let (ax, ay) = (wx - qx * t, wy - qy * t);
+        // closest point on the base
+        let t = (wx / qx).clamp(V::ZERO, V::ONE);
+        let (bx, by) = (wx - qx * t, wy - qy);
+        let k = qy.signum();
+        let d = (ax * ax + ay * ay).min(bx * bx + by * by);
+        let s = (k * (wx * qy - wy * qx)).max(k * (wy - qy));
+        d.sqrt() * s.signum()
+    }
+}
+
+/// An [`Ellipsoid`] with radii for each dimension.
+///
+/// The distance is only a bound, not exact.
+pub struct Ellipsoid<V: Comp<3>>(pub V::Vec);
+
+impl Ellipsoid<f32> {
+    pub fn widen(self) -> Ellipsoid<f32x8> {
+        Ellipsoid(self.0.widen())
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Ellipsoid<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let k0 = (p / self.0).length();
+        let k1 = (p / (self.0 * self.0)).length();
+        k0 * (k0 - V::ONE) / k1
+    }
+}
+
+/// A [`Box`] with it's edges rounded by `radius`.
+pub struct RoundBox<V: Comp<3>> {
+    /// Radii for each dimension, including the rounding.
+    pub size: V::Vec,
+    pub radius: V,
+}
+
+impl RoundBox<f32> {
+    pub fn widen(self) -> RoundBox<f32x8> {
+        RoundBox {
+            size: self.size.widen(),
+            radius: f32x8::splat(self.radius),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for RoundBox<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let q = p.abs() - self.size + self.radius;
+        q.max(V::Vec::ZERO).length() + q.max_element().min(V::ZERO) - self.radius
+    }
+}
+
+/// An [`Octahedron`] with a distance of `s` from the center to each vertex.
+pub struct Octahedron<V: Comp<3>>(pub V);
+
+impl Octahedron<f32> {
+    pub fn widen(self) -> Octahedron<f32x8> {
+        Octahedron(f32x8::splat(self.0))
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Octahedron<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let s = self.0;
+        let [x, y, z] = p.abs().to_array();
+        let m = x + y + z - s;
+        // find which axis is closest to a vertex
+        let three = V::from(3.0);
+        let cx = (three * x).simd_lt(m);
+        let cy = (three * y).simd_lt(m);
+        let cz = (three * z).simd_lt(m);
+        // swizzle so that the closest vertex is along Y
+        let qx = cx.select(x, cy.select(y, z));
+        let qy = cx.select(y, cy.select(z, x));
+        let qz = cx.select(z, cy.select(x, y));
+        let k = ((qz - qy + s) * V::from(0.5)).clamp(V::ZERO, s);
+        let d = vec3(qx, qy - s + k, qz - k).length();
+        // otherwise the closest point is on a face
+        (cx | cy | cz).select(d, m * V::from(0.57735027))
+    }
+}
+
+/// A [`HexPrism`] extruded along the Z axis.
+pub struct HexPrism<V: Comp<3>> {
+    /// Distance from the center to the middle of each side.
+    pub radius: V,
+    /// Half of the total length.
+    pub height: V,
+}
+
+impl HexPrism<f32> {
+    pub fn widen(self) -> HexPrism<f32x8> {
+        HexPrism {
+            radius: f32x8::splat(self.radius),
+            height: f32x8::splat(self.height),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for HexPrism<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        // cos(30°), sin(30°) and tan(30°)
+        let (kx, ky, kz) = (V::from(-0.8660254), V::from(0.5), V::from(0.57735));
+        let [x, y, z] = p.abs().to_array();
+        // fold the hexagon into a single side
+        let t = (kx * x + ky * y).min(V::ZERO) * V::from(2.0);
+        let (x, y) = (x - t * kx, y - t * ky);
+        let r = self.radius;
+        let dx = length2(x - x.clamp(-kz * r, kz * r), y - r) * (y - r).signum();
+        let dy = z - self.height;
+        dx.max(dy).min(V::ZERO) + length2(dx.max(V::ZERO), dy.max(V::ZERO))
+    }
+}
+
+/// A [`Triangle`] between 3 points.
+///
+/// The triangle has no thickness, so the distance is never negative.
+pub struct Triangle<V: Comp<3>> {
+    pub a: V::Vec,
+    pub b: V::Vec,
+    pub c: V::Vec,
+}
+
+impl Triangle<f32> {
+    pub fn widen(self) -> Triangle<f32x8> {
+        Triangle {
+            a: self.a.widen(),
+            b: self.b.widen(),
+            c: self.c.widen(),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Triangle<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let Self { a, b, c } = *self;
+        let (ba, pa) = (b - a, p - a);
+        let (cb, pb) = (c - b, p - b);
+        let (ac, pc) = (a - c, p - c);
+        let nor = cross::<V>(ba, ac);
+        // if `p` projects outside of the triangle the closest point is on an edge
+        let sides = side::<V>(ba, nor, pa) + side::<V>(cb, nor, pb) + side::<V>(ac, nor, pc);
+        let edge = edge_sq::<V>(ba, pa)
+            .min(edge_sq::<V>(cb, pb))
+            .min(edge_sq::<V>(ac, pc));
+        let face = nor.dot(pa) * nor.dot(pa) / nor.dot(nor);
+        sides.simd_lt(V::from(2.0)).select(edge, face).sqrt()
+    }
+}
+
+/// A [`Quad`] between 4 coplanar points.
+///
+/// The quad has no thickness, so the distance is never negative.
+pub struct Quad<V: Comp<3>> {
+    pub a: V::Vec,
+    pub b: V::Vec,
+    pub c: V::Vec,
+    pub d: V::Vec,
+}
+
+impl Quad<f32> {
+    pub fn widen(self) -> Quad<f32x8> {
+        Quad {
+            a: self.a.widen(),
+            b: self.b.widen(),
+            c: self.c.widen(),
+            d: self.d.widen(),
+        }
+    }
+}
+
+impl<V: Comp<3>> Sdf<V> for Quad<V> {
+    #[inline]
+    fn dist(&self, p: V::Vec) -> V {
+        let Self { a, b, c, d } = *self;
+        let (ba, pa) = (b - a, p - a);
+        let (cb, pb) = (c - b, p - b);
+        let (dc, pc) = (d - c, p - c);
+        let (ad, pd) = (a - d, p - d);
+        let nor = cross::<V>(ba, ad);
+        // if `p` projects outside of the quad the closest point is on an edge
+        let sides = side::<V>(ba, nor, pa)
+            + side::<V>(cb, nor, pb)
+            + side::<V>(dc, nor, pc)
+            + side::<V>(ad, nor, pd);
+        let edge = edge_sq::<V>(ba, pa)
+            .min(edge_sq::<V>(cb, pb))
+            .min(edge_sq::<V>(dc, pc))
+            .min(edge_sq::<V>(ad, pd));
+        let face = nor.dot(pa) * nor.dot(pa) / nor.dot(nor);
+        sides.simd_lt(V::from(3.0)).select(edge, face).sqrt()
+    }
+}
+
+/// Length of the 2D vector `(x, y)`.
+#[inline]
+fn length2<V: Comp<3>>(x: V, y: V) -> V {
+    (x * x + y * y).sqrt()
+}
+
+/// Which side of the `edge` the point `p` is on, relative to it's start.
+#[inline]
+fn side<V: Comp<3>>(edge: V::Vec, nor: V::Vec, p: V::Vec) -> V {
+    cross::<V>(edge, nor).dot(p).signum()
+}
+
+/// Squared distance from the `edge` to the point `p`, relative to it's start.
+#[inline]
+fn edge_sq<V: Comp<3>>(edge: V::Vec, p: V::Vec) -> V {
+    let q = edge * (edge.dot(p) / edge.dot(edge)).clamp(V::ZERO, V::ONE) - p;
+    q.dot(q)
+}
@@ -9,9 +9,26 @@ use super::{CompOps, FromFloat, Ops};
 impl Ops for f32 {}
 
 impl CompOps for f32 {
+    type Mask = bool;
+
     const ZERO: Self = 0.0;
     const ONE: Self = 1.0;
 
+    #[inline]
+    fn simd_lt(&self, other: Self) -> bool {
+        *self < other
+    }
+
+    #[inline]
+    fn simd_gt(&self, other: Self) -> bool {
+        *self > other
+    }
+
+    #[inline]
+    fn simd_eq(&self, other: Self) -> bool {
+        *self == other
+    }
+
     #[inline]
     fn min(&self, other: Self) -> Self {
         f32::min(*self, other)
@@ -31,6 +48,56 @@ impl CompOps for f32 {
     fn powf(&self, exp: Self) -> Self {
         f32::powf(*self, exp)
     }
+
+    #[inline]
+    fn abs(&self) -> Self {
+        f32::abs(*self)
+    }
+
+    #[inline]
+    fn exp(&self) -> Self {
+        f32::exp(*self)
+    }
+
+    #[inline]
+    fn ln(&self) -> Self {
+        f32::ln(*self)
+    }
+
+    #[inline]
+    fn sqrt(&self) -> Self {
+        f32::sqrt(*self)
+    }
+
+    #[inline]
+    fn sin(&self) -> Self {
+        f32::sin(*self)
+    }
+
+    #[inline]
+    fn cos(&self) -> Self {
+        f32::cos(*self)
+    }
+
+    #[inline]
+    fn acos(&self) -> Self {
+        f32::acos(*self)
+    }
+
+    #[inline]
+    fn atan2(&self, x: Self) -> Self {
+        f32::atan2(*self, x)
+    }
+
+    #[inline]
+    fn signum(&self) -> Self {
+        f32::signum(*self)
+    }
+
+    #[inline]
+    fn floor(&self) -> Self {
+        f32::floor(*self)
+    }
 }
 
 impl FromFloat for f32 {
@@ -155,6 +155,11 @@ impl Vector<2, f32> for Vec2 {
     fn powf(&self, exp: f32) -> Self {
         Vec2::powf(*self, exp)
     }
+
+    #[inline]
+    fn to_array(&self) -> [f32; 2] {
+        [self.x, self.y]
+    }
 }
 
 impl Vector2D<f32> for Vec2 {
@@ -167,6 +167,11 @@ impl Vector<3, f32> for Vec3 {
     fn powf(&self, exp: f32) -> Self {
         Vec3::powf(*self, exp)
     }
+
+    #[inline]
+    fn to_array(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
 }
 
 impl Vector3D<f32> for Vec3 {
@@ -5,14 +5,31 @@ pub use vec2::Vec2x8;
 pub use vec3::Vec3x8;
 
 use super::{CompOps, FromFloat, Ops};
-use std::simd::{f32x8, SimdFloat};
+use std::simd::{f32x8, mask32x8, SimdFloat, SimdPartialEq, SimdPartialOrd, StdFloat};
 
 impl Ops for f32x8 {}
 
 impl CompOps for f32x8 {
+    type Mask = mask32x8;
+
     const ZERO: Self = f32x8::from_array([0.0; 8]);
     const ONE: Self = f32x8::from_array([1.0; 8]);
 
+    #[inline]
+    fn simd_lt(&self, other: Self) -> mask32x8 {
+        SimdPartialOrd::simd_lt(*self, other)
+    }
+
+    #[inline]
+    fn simd_gt(&self, other: Self) -> mask32x8 {
+        SimdPartialOrd::simd_gt(*self, other)
+    }
+
+    #[inline]
+    fn simd_eq(&self, other: Self) -> mask32x8 {
+        SimdPartialEq::simd_eq(*self, other)
+    }
+
     #[inline]
     fn min(&self, other: Self) -> Self {
         f32x8::simd_min(*self, other)
@@ -32,6 +49,56 @@ impl CompOps for f32x8 {
     fn powf(&self, exp: Self) -> Self {
         powf(*self, exp)
     }
+
+    #[inline]
+    fn abs(&self) -> Self {
+        SimdFloat::abs(*self)
+    }
+
+    #[inline]
+    fn exp(&self) -> Self {
+        exp(*self)
+    }
+
+    #[inline]
+    fn ln(&self) -> Self {
+        ln(*self)
+    }
+
+    #[inline]
+    fn sqrt(&self) -> Self {
+        StdFloat::sqrt(*self)
+    }
+
+    #[inline]
+    fn sin(&self) -> Self {
+        sin(*self)
+    }
+
+    #[inline]
+    fn cos(&self) -> Self {
+        cos(*self)
+    }
+
+    #[inline]
+    fn acos(&self) -> Self {
+        acos(*self)
+    }
+
+    #[inline]
+    fn atan2(&self, x: Self) -> Self {
+        atan2(*self, x)
+    }
+
+    #[inline]
+    fn signum(&self) -> Self {
+        SimdFloat::signum(*self)
+    }
+
+    #[inline]
+    fn floor(&self) -> Self {
+        StdFloat::floor(*self)
+    }
 }
 
 impl FromFloat for std::simd::f32x8 {
@@ -52,3 +119,52 @@ fn powf(x: f32x8, y: f32x8) -> f32x8 {
     // I trust the compiler to emit the correct instructions :)
     unsafe { simd_fpow(x, y) }
 }
+
+/// Computes `e` raised to the power of `x`
+fn exp(x: f32x8) -> f32x8 {
+    extern "platform-intrinsic" {
+        fn simd_fexp<T>(x: T) -> T;
+    }
+    // SAFETY: see `powf`
+    unsafe { simd_fexp(x) }
+}
+
+/// Computes the natural logarithm of `x`
+fn ln(x: f32x8) -> f32x8 {
+    extern "platform-intrinsic" {
+        fn simd_flog<T>(x: T) -> T;
+    }
+    // SAFETY: see `powf`
+    unsafe { simd_flog(x) }
+}
+
+/// Computes the sine of `x` (in radians)
+fn sin(x: f32x8) -> f32x8 {
+    extern "platform-intrinsic" {
+        fn simd_fsin<T>(x: T) -> T;
+    }
+    // SAFETY: see `powf`
+    unsafe { simd_fsin(x) }
+}
+
+/// Computes the cosine of `x` (in radians)
+fn cos(x: f32x8) -> f32x8 {
+    extern "platform-intrinsic" {
+        fn simd_fcos<T>(x: T) -> T;
+    }
+    // SAFETY: see `powf`
+    unsafe { simd_fcos(x) }
+}
+
+/// Computes the arccosine of `x` (in radians)
+fn acos(x: f32x8) -> f32x8 {
+    // there isn't an intrinsic for this one, so each lane is done on it's own
+    f32x8::from_array(x.to_array().map(f32::acos))
+}
+
+/// Computes the four quadrant arctangent of `y` and `x` (in radians)
+fn atan2(y: f32x8, x: f32x8) -> f32x8 {
+    // see `acos`
+    let (y, x) = (y.to_array(), x.to_array());
+    f32x8::from_array(core::array::from_fn(|i| y[i].atan2(x[i])))
+}
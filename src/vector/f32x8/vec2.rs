@@ -158,6 +158,11 @@ impl Vector<2, f32x8> for Vec2x8 {
     fn powf(&self, exp: f32x8) -> Self {
         Vec2x8::powf(self, exp)
     }
+
+    #[inline]
+    fn to_array(&self) -> [f32x8; 2] {
+        [self.x, self.y]
+    }
 }
 
 impl Vector2D<f32x8> for Vec2x8 {
@@ -171,6 +171,11 @@ impl Vector<3, f32x8> for Vec3x8 {
     fn powf(&self, exp: f32x8) -> Self {
         Vec3x8::powf(self, exp)
     }
+
+    #[inline]
+    fn to_array(&self) -> [f32x8; 3] {
+        [self.x, self.y, self.z]
+    }
 }
 
 impl Vector3D<f32x8> for Vec3x8 {
@@ -0,0 +1,118 @@
+use crate::vector::{vec3, Comp, Quat, Vector};
+use core::ops::*;
+
+/// A 3x3 column major matrix.
+#[derive(Clone, Copy)]
+pub struct Mat3<V: Comp<3>> {
+    pub x_axis: V::Vec,
+    pub y_axis: V::Vec,
+    pub z_axis: V::Vec,
+}
+
+impl<V: Comp<3>> Mat3<V> {
+    #[inline(always)]
+    pub fn from_cols(x_axis: V::Vec, y_axis: V::Vec, z_axis: V::Vec) -> Self {
+        Self {
+            x_axis,
+            y_axis,
+            z_axis,
+        }
+    }
+
+    /// A matrix with `d` along the diagonal and zero elsewhere.
+    #[inline]
+    pub fn from_diagonal(d: V::Vec) -> Self {
+        let [x, y, z] = d.to_array();
+        Self::from_cols(
+            vec3(x, V::ZERO, V::ZERO),
+            vec3(V::ZERO, y, V::ZERO),
+            vec3(V::ZERO, V::ZERO, z),
+        )
+    }
+
+    /// A rotation of `angle` radians around `axis`.
+    #[inline]
+    pub fn from_axis_angle(axis: V::Vec, angle: V) -> Self {
+        Self::from_quat(Quat::from_axis_angle(axis, angle))
+    }
+
+    /// The rotation described by the normalised quaternion `q`.
+    #[inline]
+    pub fn from_quat(q: Quat<V>) -> Self {
+        let two = V::from(2.0);
+        let (x2, y2, z2) = (q.x * two, q.y * two, q.z * two);
+        let (xx, xy, xz) = (q.x * x2, q.x * y2, q.x * z2);
+        let (yy, yz, zz) = (q.y * y2, q.y * z2, q.z * z2);
+        let (wx, wy, wz) = (q.w * x2, q.w * y2, q.w * z2);
+        Self::from_cols(
+            vec3(V::ONE - (yy + zz), xy + wz, xz - wy),
+            vec3(xy - wz, V::ONE - (xx + zz), yz + wx),
+            vec3(xz + wy, yz - wx, V::ONE - (xx + yy)),
+        )
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn transpose(&self) -> Self {
+        let [xx, xy, xz] = self.x_axis.to_array();
+        let [yx, yy, yz] = self.y_axis.to_array();
+        let [zx, zy, zz] = self.z_axis.to_array();
+        Self::from_cols(vec3(xx, yx, zx), vec3(xy, yy, zy), vec3(xz, yz, zz))
+    }
+
+    /// The inverse of the matrix.
+    ///
+    /// The result is undefined when the matrix is singular, when its determinant is zero.
+    #[must_use]
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        let [xx, xy, xz] = self.x_axis.to_array();
+        let [yx, yy, yz] = self.y_axis.to_array();
+        let [zx, zy, zz] = self.z_axis.to_array();
+        // rows of the inverse are the cross products of the columns
+        let r0 = vec3(yy * zz - yz * zy, yz * zx - yx * zz, yx * zy - yy * zx);
+        let r1 = vec3(zy * xz - zz * xy, zz * xx - zx * xz, zx * xy - zy * xx);
+        let r2 = vec3(xy * yz - xz * yy, xz * yx - xx * yz, xx * yy - xy * yx);
+        let det = self.z_axis.dot(r2);
+        Self::from_cols(r0, r1, r2).transpose() / det
+    }
+
+    /// Transforms the vector `v`.
+    #[inline]
+    pub fn mul_vec3(&self, v: V::Vec) -> V::Vec {
+        let [x, y, z] = v.to_array();
+        self.x_axis * x + self.y_axis * y + self.z_axis * z
+    }
+
+    #[inline]
+    pub fn mul_mat3(&self, rhs: Self) -> Self {
+        Self::from_cols(
+            self.mul_vec3(rhs.x_axis),
+            self.mul_vec3(rhs.y_axis),
+            self.mul_vec3(rhs.z_axis),
+        )
+    }
+}
+
+impl<V: Comp<3>> Mul for Mat3<V> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        self.mul_mat3(rhs)
+    }
+}
+
+impl<V: Comp<3>> Div<V> for Mat3<V> {
+    type Output = Self;
+    #[inline]
+    fn div(self, rhs: V) -> Self {
+        Self::from_cols(self.x_axis / rhs, self.y_axis / rhs, self.z_axis / rhs)
+    }
+}
+
+impl<V: Comp<3>> From<Quat<V>> for Mat3<V> {
+    #[inline]
+    fn from(q: Quat<V>) -> Self {
+        Mat3::from_quat(q)
+    }
+}
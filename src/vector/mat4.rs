@@ -0,0 +1,96 @@
+use crate::vector::{vec3, Comp, Mat3, Quat, Vector};
+use core::ops::*;
+
+/// A 4x4 column major matrix.
+///
+/// Used for affine transformations of 3D points,
+/// so the bottom row is expected to be `[0, 0, 0, 1]`.
+#[derive(Clone, Copy)]
+pub struct Mat4<V: Comp<3>> {
+    pub x_axis: [V; 4],
+    pub y_axis: [V; 4],
+    pub z_axis: [V; 4],
+    pub w_axis: [V; 4],
+}
+
+impl<V: Comp<3>> Mat4<V> {
+    #[inline(always)]
+    pub fn from_cols(x_axis: [V; 4], y_axis: [V; 4], z_axis: [V; 4], w_axis: [V; 4]) -> Self {
+        Self {
+            x_axis,
+            y_axis,
+            z_axis,
+            w_axis,
+        }
+    }
+
+    /// An affine transformation with a linear part `m` followed by a `translation`.
+    #[inline]
+    pub fn from_mat3_translation(m: Mat3<V>, translation: V::Vec) -> Self {
+        let col = |v: V::Vec, w: V| {
+            let [x, y, z] = v.to_array();
+            [x, y, z, w]
+        };
+        Self::from_cols(
+            col(m.x_axis, V::ZERO),
+            col(m.y_axis, V::ZERO),
+            col(m.z_axis, V::ZERO),
+            col(translation, V::ONE),
+        )
+    }
+
+    /// Scales, then rotates, then translates.
+    #[inline]
+    pub fn from_scale_rotation_translation(
+        scale: V::Vec,
+        rotation: Quat<V>,
+        translation: V::Vec,
+    ) -> Self {
+        let m = Mat3::from_quat(rotation) * Mat3::from_diagonal(scale);
+        Self::from_mat3_translation(m, translation)
+    }
+
+    /// The upper left 3x3 part of the matrix.
+    #[inline]
+    pub fn mat3(&self) -> Mat3<V> {
+        let axis = |[x, y, z, _]: [V; 4]| vec3(x, y, z);
+        Mat3::from_cols(axis(self.x_axis), axis(self.y_axis), axis(self.z_axis))
+    }
+
+    /// The translation part of the matrix.
+    #[inline]
+    pub fn translation(&self) -> V::Vec {
+        let [x, y, z, _] = self.w_axis;
+        vec3(x, y, z)
+    }
+
+    /// The inverse of the affine transformation.
+    ///
+    /// The result is undefined when the matrix is singular.
+    #[must_use]
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        let m = self.mat3().inverse();
+        Self::from_mat3_translation(m, -m.mul_vec3(self.translation()))
+    }
+
+    /// Transforms the point `p`, applying the translation.
+    #[inline]
+    pub fn transform_point3(&self, p: V::Vec) -> V::Vec {
+        self.mat3().mul_vec3(p) + self.translation()
+    }
+
+    #[inline]
+    pub fn mul_mat4(&self, rhs: Self) -> Self {
+        let m = self.mat3();
+        Self::from_mat3_translation(m * rhs.mat3(), self.transform_point3(rhs.translation()))
+    }
+}
+
+impl<V: Comp<3>> Mul for Mat4<V> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        self.mul_mat4(rhs)
+    }
+}
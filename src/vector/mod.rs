@@ -1,13 +1,22 @@
 mod f32;
 mod f32x8;
+mod mat3;
+mod mat4;
+mod quat;
 
+pub use mat3::Mat3;
+pub use mat4::Mat4;
+pub use quat::Quat;
+
+use self::f32::Vec3;
 use core::ops::*;
 
 pub mod prelude {
     pub use super::f32::*;
     pub use super::f32x8::*;
-    pub use super::{broadcast, vec2, vec3};
+    pub use super::{basis, broadcast, cross, from_vec3, select, vec2, vec3};
     pub use super::{Comp, Maskable, Vector, Vector2D, Vector3D};
+    pub use super::{Mat3, Mat4, Quat};
 }
 
 pub trait Ops<I = Self, O = Self>:
@@ -27,13 +36,37 @@ pub trait Ops<I = Self, O = Self>:
 }
 
 pub trait CompOps: Ops {
+    /// The result of comparing components, used to select between them.
+    type Mask: Copy
+        + Send
+        + Sync
+        + Maskable<Self>
+        + BitAnd<Output = Self::Mask>
+        + BitOr<Output = Self::Mask>
+        + Not<Output = Self::Mask>;
+
     const ZERO: Self;
     const ONE: Self;
 
+    fn simd_lt(&self, other: Self) -> Self::Mask;
+    fn simd_gt(&self, other: Self) -> Self::Mask;
+    fn simd_eq(&self, other: Self) -> Self::Mask;
+
     fn min(&self, other: Self) -> Self;
     fn max(&self, other: Self) -> Self;
     fn clamp(&self, min: Self, max: Self) -> Self;
     fn powf(&self, exp: Self) -> Self;
+    fn abs(&self) -> Self;
+    fn exp(&self) -> Self;
+    fn ln(&self) -> Self;
+    fn sqrt(&self) -> Self;
+    fn sin(&self) -> Self;
+    fn cos(&self) -> Self;
+    fn acos(&self) -> Self;
+    /// The four quadrant arctangent of `self` and `x`, the angle of the point `(x, self)`.
+    fn atan2(&self, x: Self) -> Self;
+    fn signum(&self) -> Self;
+    fn floor(&self) -> Self;
 }
 
 /// An N dimensional Vector containing components of type T.
@@ -55,6 +88,7 @@ where
     fn min(&self, other: Self) -> Self;
     fn min_element(&self) -> T;
     fn powf(&self, exp: T) -> Self;
+    fn to_array(&self) -> [T; N];
 }
 
 pub trait Vector2D<T>: Vector<2, T>
@@ -91,6 +125,10 @@ pub trait FromFloat {
 /// A mask type
 pub trait Maskable<T> {
     fn select(&self, true_values: T, false_values: T) -> T;
+    /// If any of the lanes are set.
+    fn any(&self) -> bool;
+    /// If all of the lanes are set.
+    fn all(&self) -> bool;
 }
 
 impl<T> Maskable<T> for bool {
@@ -102,6 +140,16 @@ impl<T> Maskable<T> for bool {
             false_values
         }
     }
+
+    #[inline]
+    fn any(&self) -> bool {
+        *self
+    }
+
+    #[inline]
+    fn all(&self) -> bool {
+        *self
+    }
 }
 
 impl<T, M, const LANES: usize> Maskable<std::simd::Simd<T, LANES>> for std::simd::Mask<M, LANES>
@@ -118,6 +166,16 @@ where
     ) -> std::simd::Simd<T, LANES> {
         Self::select(*self, true_values, false_values)
     }
+
+    #[inline]
+    fn any(&self) -> bool {
+        Self::any(*self)
+    }
+
+    #[inline]
+    fn all(&self) -> bool {
+        Self::all(*self)
+    }
 }
 
 /// Broadcast a single element across all components of the vector.
@@ -126,6 +184,17 @@ pub fn broadcast<const N: usize, V: Comp<N>>(val: V) -> V::Vec {
     V::new_vec([val; N])
 }
 
+/// Selects each component from `true_values` where the `mask` is set, otherwise from `false_values`.
+#[inline]
+pub fn select<const N: usize, V: Comp<N>>(
+    mask: V::Mask,
+    true_values: V::Vec,
+    false_values: V::Vec,
+) -> V::Vec {
+    let (t, f) = (true_values.to_array(), false_values.to_array());
+    V::new_vec(core::array::from_fn(|i| mask.select(t[i], f[i])))
+}
+
 /// Creates a new 2D vector from it components.
 #[inline]
 pub fn vec2<V: Comp<2>>(x: V, y: V) -> V::Vec {
@@ -137,3 +206,32 @@ pub fn vec2<V: Comp<2>>(x: V, y: V) -> V::Vec {
 pub fn vec3<V: Comp<3>>(x: V, y: V, z: V) -> V::Vec {
     V::new_vec([x, y, z])
 }
+
+/// Converts a scalar 3D vector into a 3D vector of any component.
+#[inline]
+pub fn from_vec3<V: Comp<3>>(v: Vec3) -> V::Vec {
+    vec3(V::from(v.x), V::from(v.y), V::from(v.z))
+}
+
+/// The cross product of two 3D vectors.
+#[inline]
+pub fn cross<V: Comp<3>>(a: V::Vec, b: V::Vec) -> V::Vec {
+    let [ax, ay, az] = a.to_array();
+    let [bx, by, bz] = b.to_array();
+    vec3(ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx)
+}
+
+/// Two vectors that are perpendicular to each other and the unit vector `n`.
+///
+/// https://graphics.pixar.com/library/OrthonormalB/paper.pdf
+#[inline]
+pub fn basis<V: Comp<3>>(n: V::Vec) -> (V::Vec, V::Vec) {
+    let [x, y, z] = n.to_array();
+    let sign = z.signum();
+    let a = -V::ONE / (sign + z);
+    let b = x * y * a;
+    (
+        vec3(V::ONE + sign * x * x * a, sign * b, -sign * x),
+        vec3(b, sign + y * y * a, -y),
+    )
+}
@@ -0,0 +1,42 @@
+use crate::vector::{Comp, Vector};
+use core::ops::*;
+
+/// A Quaternion representing a rotation in 3D space.
+#[derive(Clone, Copy)]
+pub struct Quat<V: Comp<3>> {
+    pub x: V,
+    pub y: V,
+    pub z: V,
+    pub w: V,
+}
+
+impl<V: Comp<3>> Quat<V> {
+    #[inline(always)]
+    pub fn new(x: V, y: V, z: V, w: V) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// A rotation of `angle` radians around `axis`.
+    #[inline]
+    pub fn from_axis_angle(axis: V::Vec, angle: V) -> Self {
+        let half = angle * V::from(0.5);
+        let (s, c) = (half.sin(), half.cos());
+        let [x, y, z] = (axis.normalise() * s).to_array();
+        Self::new(x, y, z, c)
+    }
+}
+
+impl<V: Comp<3>> Mul for Quat<V> {
+    type Output = Self;
+    /// Combines two rotations, `rhs` is applied first.
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self, rhs);
+        Self::new(
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        )
+    }
+}